@@ -0,0 +1,49 @@
+//! Checks that things which should always be true about rings and boards actually are.
+//!
+//! Run with `cargo fuzz run invariants`.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use ringtactoe::Board;
+use ringtactoe::Glyph;
+use ringtactoe::Literal;
+use ringtactoe::Position;
+
+fuzz_target!(|input: (Board, u8, Glyph)| {
+    let (board, n, glyph) = input;
+    let ring = board.ring;
+
+    // Canonicalizing something that's already canonical shouldn't change it.
+    let canonical = ring.canonicalize();
+    assert!(Literal(canonical.canonicalize()) == Literal(canonical));
+    assert!(canonical == ring);
+
+    // Shifting one way and then back should get back the same ring, even when `n` is bigger than the ring.
+    assert!(Literal((ring << n) >> n) == Literal(ring));
+    assert!(Literal((ring >> n) << n) == Literal(ring));
+    assert_eq!((ring << n).get(0), ring.get(n));
+
+    // `set` should only change the one cell, wrapping around the same way as `get`.
+    let mut set = ring;
+    set.set(n, glyph);
+    assert_eq!(set.get(n), glyph);
+    for i in 0..ring.len() {
+        if i != n % ring.len() {
+            assert_eq!(set.get(i), ring.get(i));
+        }
+    }
+
+    // `winner` should agree with `wins`, and every win should actually be made up of the winner's glyphs.
+    let wins = board.wins();
+    assert_eq!(board.winner(), wins.first().map(|win| win.winner()));
+    for win in wins {
+        for pos in win.cells(ring.len()) {
+            let cell = match pos {
+                Position::Center => board.center,
+                Position::Ring(i) => ring.get(i),
+            };
+            assert_eq!(cell, win.winner());
+        }
+    }
+});
@@ -0,0 +1,137 @@
+//! A version of the game which runs in the terminal, for when there's no graphics available.
+//!
+//! Usage: `cli [cells] [--ai x|o] [--depth n] [--code code]`
+//!
+//! `cells` defaults to 8. Passing `--ai` makes the computer play as that glyph.
+//! `--code` starts from a position shared with `Board::to_code`, which gets printed along with the board.
+//! By default it searches the whole game tree, which is too slow for big rings; `--depth` limits how far ahead it looks.
+
+use std::env;
+use std::io;
+use std::io::BufRead;
+use std::io::Write;
+use std::process;
+
+use ringtactoe::Board;
+use ringtactoe::GameResult;
+use ringtactoe::Glyph;
+use ringtactoe::Player;
+use ringtactoe::Position;
+
+fn usage() -> ! {
+    eprintln!("usage: cli [cells] [--ai x|o] [--depth n] [--code code]");
+    process::exit(1);
+}
+
+fn print_board(board: &Board) {
+    // Dots make it much easier to count along the ring than spaces do.
+    let center = match board.center {
+        Glyph::None => '.',
+        glyph => glyph.to_char(),
+    };
+    println!("center: [{}]", center);
+    println!("ring:   [{}]", board.ring.fmt_with('.', 'X', 'O'));
+
+    // Put the indices underneath, so that people know what to type.
+    // Only the last digit fits, but that's enough to count along with.
+    let indices: String = (0..board.ring.len())
+        .map(|i| char::from(b'0' + i % 10))
+        .collect();
+    println!("         {}", indices);
+    println!("code:    {}", board.to_code());
+}
+
+/// Returns `None` if the input couldn't be understood.
+fn parse_move(input: &str) -> Option<Position> {
+    let input = input.trim();
+    if input.eq_ignore_ascii_case("c") || input.eq_ignore_ascii_case("center") {
+        Some(Position::Center)
+    } else {
+        input.parse().ok().map(Position::Ring)
+    }
+}
+
+fn main() {
+    let mut cells: u8 = 8;
+    let mut ai = None;
+    let mut depth = u8::MAX;
+    let mut code = None;
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "--ai" {
+            ai = match args.next().as_deref() {
+                Some("x") | Some("X") => Some(Player::X),
+                Some("o") | Some("O") => Some(Player::O),
+                _ => usage(),
+            };
+        } else if arg == "--depth" {
+            depth = args
+                .next()
+                .and_then(|depth| depth.parse().ok())
+                .unwrap_or_else(|| usage());
+        } else if arg == "--code" {
+            code = Some(args.next().unwrap_or_else(|| usage()));
+        } else {
+            cells = arg.parse().unwrap_or_else(|_| usage());
+        }
+    }
+
+    let board = match code {
+        Some(code) => Board::from_code(&code).map_err(|err| err.to_string()),
+        None => Board::try_new(cells).map_err(|err| err.to_string()),
+    };
+    let mut board = board.unwrap_or_else(|err| {
+        eprintln!("{}", err);
+        process::exit(1);
+    });
+
+    let stdin = io::stdin();
+    let mut lines = stdin.lock().lines();
+
+    while board.result() == GameResult::InProgress {
+        print_board(&board);
+
+        let turn = board.turn();
+
+        if Some(turn) == ai {
+            // The game isn't over, so there's always a move.
+            let mv = board.best_move_depth(depth).unwrap();
+            match mv {
+                Position::Center => println!("{} plays in the center", turn),
+                Position::Ring(i) => println!("{} plays at {}", turn, i),
+            }
+            board.play(mv).unwrap();
+            continue;
+        }
+
+        print!("{} to play (c for center, or a ring index): ", turn);
+        io::stdout().flush().unwrap();
+
+        let line = match lines.next() {
+            Some(line) => line.unwrap(),
+            // stdin was closed, so there's no way to keep playing.
+            None => return,
+        };
+
+        let mv = match parse_move(&line) {
+            Some(mv) => mv,
+            None => {
+                println!("I don't know what '{}' means.", line.trim());
+                continue;
+            }
+        };
+
+        if let Err(err) = board.play(mv) {
+            println!("You can't play there: {}.", err);
+        }
+    }
+
+    print_board(&board);
+
+    match board.result() {
+        GameResult::Won(player) => println!("{} wins!", player),
+        GameResult::Draw => println!("It's a draw."),
+        GameResult::InProgress => unreachable!(),
+    }
+}
@@ -0,0 +1,90 @@
+//! Chess-clock style time controls.
+//!
+//! Each player starts with the same amount of time, which runs down while it's their turn, and gets an increment added
+//! on every time they finish a move. If it runs out (their flag falls) they lose.
+//!
+//! This doesn't read the time itself; whatever's running the game says how much has passed (see `Game::tick`). That
+//! way the tests don't have to actually wait around.
+
+use core::time::Duration;
+
+use crate::game::index;
+use crate::Player;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Clock {
+    /// How much time each player starts with.
+    initial: Duration,
+    /// How much time gets added to a player's clock after each of their moves.
+    increment: Duration,
+    /// How much time each player has left, indexed by `game::index`.
+    remaining: [Duration; 2],
+}
+
+impl Clock {
+    /// Create a clock where both players start with `initial` and get `increment` added after each of their moves.
+    ///
+    /// Returns `None` if `initial` is zero, since then both players would have run out before anyone had moved.
+    pub fn new(initial: Duration, increment: Duration) -> Option<Self> {
+        if initial.is_zero() {
+            return None;
+        }
+        Some(Self {
+            initial,
+            increment,
+            remaining: [initial; 2],
+        })
+    }
+
+    pub fn initial(&self) -> Duration {
+        self.initial
+    }
+
+    pub fn increment(&self) -> Duration {
+        self.increment
+    }
+
+    /// How much time `player` has left.
+    pub fn remaining(&self, player: Player) -> Duration {
+        self.remaining[index(player)]
+    }
+
+    /// Whether `player` has run out of time.
+    pub fn is_flagged(&self, player: Player) -> bool {
+        self.remaining(player).is_zero()
+    }
+
+    /// Whoever's run out of time, if anyone has.
+    pub fn flagged(&self) -> Option<Player> {
+        [Player::X, Player::O]
+            .iter()
+            .copied()
+            .find(|&player| self.is_flagged(player))
+    }
+
+    /// Take `elapsed` off `player`'s time, stopping at zero.
+    ///
+    /// Returns whether they've still got any time left.
+    pub fn spend(&mut self, player: Player, elapsed: Duration) -> bool {
+        let remaining = &mut self.remaining[index(player)];
+        *remaining = remaining.saturating_sub(elapsed);
+        !remaining.is_zero()
+    }
+
+    /// Add the increment to `player`'s time, for when they've finished a move.
+    ///
+    /// This does nothing once they've run out, since by then they've already lost. It stops at `Duration::MAX` rather
+    /// than overflowing, since both durations can come from anywhere.
+    pub fn finish_move(&mut self, player: Player) {
+        if !self.is_flagged(player) {
+            let remaining = &mut self.remaining[index(player)];
+            *remaining = remaining.saturating_add(self.increment);
+        }
+    }
+
+    /// Put both players back to their initial time.
+    pub fn reset(&mut self) {
+        self.remaining = [self.initial; 2];
+    }
+}
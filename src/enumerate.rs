@@ -0,0 +1,99 @@
+//! Going through every position of a given size, one of each up to rotations and reflections.
+//!
+//! The iterators here are all built on `Ring::canonical_index`, so they come out in the same order as the indices, and have the same
+//! limit of 20 cells. Like it, they need the `std` feature.
+
+#[cfg(feature = "std")]
+use crate::Board;
+use crate::Ring;
+
+/// Iterate over every canonical ring with `cells` cells, in order of `Ring::canonical_index`.
+///
+/// Panics if `cells` is more than 20.
+#[cfg(feature = "std")]
+pub fn canonical_rings(cells: u8) -> impl Iterator<Item = Ring> {
+    (0..Ring::canonical_count(cells))
+        .map(move |index| Ring::from_canonical_index(cells, index).unwrap())
+}
+
+/// Iterate over every canonical board with `cells` cells in its ring, in order of `Board::canonical_index`.
+///
+/// This includes boards which could never come up in a real game; see `reachable_boards` for only the ones which can.
+///
+/// Panics if `cells` is more than 20.
+#[cfg(feature = "std")]
+pub fn canonical_boards(cells: u8) -> impl Iterator<Item = Board> {
+    (0..Board::canonical_count(cells))
+        .map(move |index| Board::from_canonical_index(cells, index).unwrap())
+}
+
+/// Iterate over every canonical board with `cells` cells in its ring which can come up in an actual game
+/// (see `Board::is_reachable`), including the ones where the game's over.
+///
+/// Panics if `cells` is more than 20.
+#[cfg(feature = "std")]
+pub fn reachable_boards(cells: u8) -> impl Iterator<Item = Board> {
+    // Rotating or reflecting a board doesn't change whether it's reachable, so checking the canonical ones is enough.
+    canonical_boards(cells).filter(Board::is_reachable)
+}
+
+/// Work out how many canonical rings there are with `cells` cells, without going through them.
+///
+/// This uses Burnside's lemma: the number of rings that are different up to symmetry is the average number of rings left
+/// unchanged by each symmetry. Unlike `Ring::canonical_count`, it works for every size of ring.
+///
+/// Panics if `cells` is 0 or more than `Ring::MAX_CELLS`.
+pub fn count_canonical_rings(cells: u8) -> u128 {
+    assert!(
+        cells > 0 && cells <= Ring::MAX_CELLS,
+        "invalid number of cells"
+    );
+    let n = u32::from(cells);
+
+    // A rotation by `k` splits the ring into `gcd(n, k)` cycles, each of which has to be all the same glyph.
+    // Going through the divisors rather than every `k` means there are never more than 80 big powers to add up.
+    let rotations: u128 = (1..=n)
+        .filter(|&d| n.is_multiple_of(d))
+        .map(|d| u128::from(totient(d)) * 3u128.pow(n / d))
+        .sum();
+
+    // A reflection fixes the cells its axis goes through and swaps the rest in pairs.
+    let reflections = if !n.is_multiple_of(2) {
+        // Every axis goes through one cell.
+        u128::from(n) * 3u128.pow(n.div_ceil(2))
+    } else {
+        // Half the axes go through two cells and half go between them.
+        u128::from(n / 2) * (3u128.pow(n / 2 + 1) + 3u128.pow(n / 2))
+    };
+
+    (rotations + reflections) / (2 * u128::from(n))
+}
+
+/// Work out how many canonical boards there are with `cells` cells in the ring, without going through them.
+///
+/// The center isn't affected by any of the symmetries, so this is just 3 times `count_canonical_rings`.
+///
+/// Panics if `cells` is 0 or more than `Ring::MAX_CELLS`.
+pub fn count_canonical_boards(cells: u8) -> u128 {
+    count_canonical_rings(cells) * 3
+}
+
+/// Euler's totient function: how many numbers in `1..=n` don't share any factors with `n`.
+fn totient(n: u32) -> u32 {
+    let mut result = n;
+    let mut n = n;
+    let mut p = 2;
+    while p * p <= n {
+        if n.is_multiple_of(p) {
+            while n.is_multiple_of(p) {
+                n /= p;
+            }
+            result -= result / p;
+        }
+        p += 1;
+    }
+    if n > 1 {
+        result -= result / n;
+    }
+    result
+}
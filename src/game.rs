@@ -0,0 +1,535 @@
+//! A game in progress: a `Board`, plus whose turn it is and how it got there.
+//!
+//! `Board::play` works out whose turn it is from the glyphs every time, which is fine for searching, but anything
+//! actually running a game (like the GUI) wants something which keeps track of it and won't let the players get out of
+//! order. That's what `Game` is for. It also remembers the moves that were made, so they can be taken back, and
+//! follows whichever `RuleSet` it was started with rather than always using the normal rules.
+//!
+//! Anything which wants to know when the game changes, like a UI or a logger, can register a listener with
+//! `Game::on_event` instead of checking the board every frame.
+//!
+//! A game can also be timed with a `clock::Clock`, in which case running out of time loses.
+
+use alloc::boxed::Box;
+use alloc::collections::VecDeque;
+use alloc::vec::Vec;
+use core::fmt;
+use core::fmt::Debug;
+use core::fmt::Formatter;
+use core::mem;
+use core::time::Duration;
+
+use crate::clock::Clock;
+use crate::rules::RuleError;
+use crate::rules::RuleSet;
+use crate::rules::Variant;
+#[cfg(feature = "std")]
+use crate::solver::Solver;
+use crate::Board;
+use crate::Direction;
+use crate::GameStatus;
+use crate::Glyph;
+use crate::Move;
+use crate::MoveError;
+use crate::Player;
+use crate::Position;
+
+/// Something that happened in a `Game`, which gets passed to the listeners registered with `Game::on_event`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum GameEvent {
+    /// A move was made, either by playing it or by redoing it.
+    MovePlayed(Move),
+    /// A move was taken back with `Game::undo`.
+    Undone(Move),
+    /// The move that was just played won the game for this player. This always comes straight after its `MovePlayed`.
+    Won(Player),
+    /// The move that was just played filled up the board without anyone winning. This always comes straight after its
+    /// `MovePlayed`.
+    Drawn,
+    /// This player ran out of time while it was their turn (see `Game::tick`), so the other player won.
+    OutOfTime(Player),
+}
+
+pub struct Game {
+    rules: RuleSet,
+    board: Board,
+    turn: Player,
+    /// The cells each player has put glyphs in that are still on the board, oldest first, indexed by `index`.
+    placed: [VecDeque<Position>; 2],
+    /// Every move made so far, oldest first.
+    history: Vec<Move>,
+    /// How things were before each move in `history`, for undoing them and spotting repetitions.
+    positions: Vec<Snapshot>,
+    /// Moves which have been undone, most recently undone last, so that they can be redone.
+    undone: Vec<Move>,
+    listeners: Vec<Box<dyn FnMut(GameEvent)>>,
+    clock: Option<Clock>,
+}
+
+impl Game {
+    /// Start a new game with `rules`, on a blank board. X goes first.
+    ///
+    /// Panics if the rules aren't valid; see `RuleSet::validate` for the reasons why.
+    pub fn new(rules: RuleSet) -> Self {
+        match Self::try_new(rules) {
+            Ok(game) => game,
+            Err(err) => panic!("invalid rules: {}", err),
+        }
+    }
+
+    /// Like `new`, but returning an error instead of panicking if the rules aren't valid.
+    pub fn try_new(rules: RuleSet) -> Result<Self, RuleError> {
+        rules.validate()?;
+        Ok(Self::with_rules(rules, Board::new(rules.cells)))
+    }
+
+    /// Carry on a game with the normal rules from `board`, which might already have some glyphs on it.
+    ///
+    /// The board doesn't say whose turn it is, so it's worked out from the glyphs the same way as `Board::turn`.
+    /// It doesn't say how it got there either, so the history starts out empty and the glyphs already on it can't be
+    /// undone.
+    pub fn from_board(board: Board) -> Self {
+        Self::with_rules(RuleSet::new(board.ring.len()), board)
+    }
+
+    fn with_rules(rules: RuleSet, board: Board) -> Self {
+        Self {
+            rules,
+            board,
+            turn: board.turn(),
+            placed: Default::default(),
+            history: Vec::new(),
+            positions: Vec::new(),
+            undone: Vec::new(),
+            listeners: Vec::new(),
+            clock: None,
+        }
+    }
+
+    /// Register `listener` to be called with every `GameEvent` from now on.
+    ///
+    /// Listeners are called in the order they were registered, after the game has already changed, so they see the
+    /// new state if they look at it afterwards.
+    pub fn on_event(&mut self, listener: impl FnMut(GameEvent) + 'static) {
+        self.listeners.push(Box::new(listener));
+    }
+
+    /// The rules this game is being played with.
+    pub fn rules(&self) -> &RuleSet {
+        &self.rules
+    }
+
+    /// The clock this game is being timed with, if it's timed at all.
+    pub fn clock(&self) -> Option<&Clock> {
+        self.clock.as_ref()
+    }
+
+    /// Start timing the game with `clock`, replacing any clock it already had.
+    pub fn set_clock(&mut self, clock: Clock) {
+        self.clock = Some(clock);
+    }
+
+    /// The board as it currently stands.
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    /// Whose turn it is. Once the game's over, this is whoever would have gone next.
+    pub fn turn(&self) -> Player {
+        self.turn
+    }
+
+    /// The cells that `player` has put glyphs in which are still there, oldest first.
+    ///
+    /// With `Variant::Fading`, the first of these is the one that disappears the next time they put one down. This
+    /// doesn't include anything that was already on the board when the game started.
+    pub fn placed(&self, player: Player) -> impl Iterator<Item = Position> + '_ {
+        self.placed[index(player)].iter().copied()
+    }
+
+    /// The moves that have been made so far, oldest first, not including any which have been undone.
+    pub fn history(&self) -> &[Move] {
+        &self.history
+    }
+
+    /// Whether the game is still going, and if not, how it ended.
+    ///
+    /// This follows the game's rules, so it can be different to what `Board::status` says about the same board.
+    pub fn status(&self) -> GameStatus {
+        if let Some(player) = self.clock.as_ref().and_then(Clock::flagged) {
+            return GameStatus::Won(player.opponent());
+        }
+
+        let status = self.rules.status(&self.board);
+        if status != GameStatus::InProgress {
+            return status;
+        }
+
+        if let Variant::Morris { pieces } = self.rules.variant {
+            let placing = self.placed[index(self.turn)].len() < usize::from(pieces);
+            if !placing && !self.can_slide() {
+                return GameStatus::Won(self.turn.opponent());
+            }
+            // It's the third time if it's already come up twice before.
+            let repeats = self
+                .positions
+                .iter()
+                .filter(|earlier| earlier.turn == self.turn)
+                .filter(|earlier| earlier.board.is_identical(&self.board))
+                .count();
+            if repeats >= 2 {
+                return GameStatus::Draw;
+            }
+        }
+
+        status
+    }
+
+    /// Whether the game has finished, either with a win or a draw.
+    pub fn is_over(&self) -> bool {
+        self.status() != GameStatus::InProgress
+    }
+
+    /// Find the best move for whoever's turn it is, like `Board::best_move`, except that it plays misère if the rules
+    /// say to.
+    ///
+    /// Misère is the only change to the rules that the search knows about, so this returns `None` for any other
+    /// variant, or if the center's turned off or the win length isn't 3, rather than suggesting moves for the wrong
+    /// game.
+    #[cfg(feature = "std")]
+    pub fn best_move(&self) -> Option<Position> {
+        let supported = RuleSet {
+            misere: self.rules.misere,
+            ..RuleSet::new(self.rules.cells)
+        };
+        if self.rules != supported {
+            return None;
+        }
+
+        let mut solver = if self.rules.misere {
+            Solver::misere()
+        } else {
+            Solver::new()
+        };
+        solver
+            .best_move(&self.board)
+            .filter(|&pos| self.rules.allows(Move::place(pos, self.turn)))
+    }
+
+    /// Take `elapsed` off the clock of whoever's turn it is, if the game's timed and still going.
+    ///
+    /// Whatever's running the game should call this with however long it's been since the last call (or since the
+    /// last move, if that was more recent) before it tries to make a move, and every so often while it's waiting for
+    /// one. If the player's time runs out, they lose, and any moves they try to make afterwards get
+    /// `MoveError::GameOver`.
+    ///
+    /// Returns the status of the game afterwards.
+    pub fn tick(&mut self, elapsed: Duration) -> GameStatus {
+        if self.is_over() {
+            return self.status();
+        }
+        let turn = self.turn;
+        if let Some(clock) = &mut self.clock {
+            if !clock.spend(turn, elapsed) {
+                self.emit(GameEvent::OutOfTime(turn));
+            }
+        }
+        self.status()
+    }
+
+    /// Put the glyph of whoever's turn it is at `pos`, and pass the turn to the other player.
+    ///
+    /// Returns the move that was made, or an error if the game's over, the cell isn't empty or the rules don't allow
+    /// it, in which case nothing changes and it's still the same player's turn.
+    ///
+    /// This starts a new line of play, so any moves which were undone can't be redone anymore.
+    pub fn play(&mut self, pos: Position) -> Result<Move, MoveError> {
+        self.submit(Move::place(pos, self.turn))
+    }
+
+    /// Like `play`, but putting `glyph` at `pos` instead of the glyph of whoever's turn it is.
+    ///
+    /// Only `rules::Variant::OrderAndChaos` allows putting down the other player's glyph.
+    pub fn place(&mut self, pos: Position, glyph: Glyph) -> Result<Move, MoveError> {
+        self.submit(Move::Place {
+            pos,
+            player: self.turn,
+            glyph,
+        })
+    }
+
+    /// Turn the ring one cell in `direction` for whoever's turn it is, instead of putting a glyph anywhere.
+    ///
+    /// This is only allowed with `rules::Variant::Rotation`, and not if it would make the board exactly the same as it
+    /// was earlier on with the same player to move; otherwise the players could keep turning it back and forth forever.
+    pub fn rotate(&mut self, direction: Direction) -> Result<Move, MoveError> {
+        self.submit(Move::Rotate {
+            player: self.turn,
+            direction,
+        })
+    }
+
+    /// Move one of the glyphs of whoever's turn it is from `from` to `to`, which has to be empty and next to it.
+    ///
+    /// This is only allowed with `rules::Variant::Morris`, once the player's put down all their pieces.
+    pub fn slide(&mut self, from: Position, to: Position) -> Result<Move, MoveError> {
+        self.submit(Move::Slide {
+            player: self.turn,
+            from,
+            to,
+        })
+    }
+
+    /// Like `play`, `rotate` or `slide`, but for a `Move` which says who's making it, which has to be whoever's turn it
+    /// is.
+    pub fn apply(&mut self, mv: Move) -> Result<(), MoveError> {
+        self.submit(mv).map(|_| ())
+    }
+
+    /// Take back the last move, giving the turn back to whoever made it.
+    ///
+    /// Returns the move that was taken back, or `None` if there weren't any moves to take back.
+    ///
+    /// This doesn't wind the clock back, since the time spent on the move has still gone.
+    pub fn undo(&mut self) -> Option<Move> {
+        let mv = self.history.pop()?;
+        let before = self
+            .positions
+            .pop()
+            .expect("there should be a position for every move");
+        self.board = before.board;
+        self.turn = before.turn;
+        self.placed = before.placed;
+        self.undone.push(mv);
+        self.emit(GameEvent::Undone(mv));
+        Some(mv)
+    }
+
+    /// Make the last move that was taken back with `undo` again.
+    ///
+    /// Returns the move that was made, or `None` if there's nothing to redo, either because nothing's been undone or
+    /// because a different move has been played since. It's also `None` if the game's over, which can happen after an
+    /// undo if somebody runs out of time.
+    pub fn redo(&mut self) -> Option<Move> {
+        if self.is_over() {
+            return None;
+        }
+        let mv = self.undone.pop()?;
+        self.make(mv)
+            .expect("undone moves should still be legal after undoing");
+        Some(mv)
+    }
+
+    /// Empty the board and start again from X's turn, keeping the same number of cells in the ring.
+    ///
+    /// This forgets the whole history, so none of it can be undone or redone, and puts the clock back to the start too.
+    pub fn restart(&mut self) {
+        if let Some(clock) = &mut self.clock {
+            clock.reset();
+        }
+        self.board.clear();
+        self.turn = Player::X;
+        self.placed = Default::default();
+        self.history.clear();
+        self.positions.clear();
+        self.undone.clear();
+    }
+
+    /// Make a new move, which means the ones that were undone can't be redone anymore.
+    ///
+    /// This is also when the player who made it gets their increment, so that redoing a move doesn't give it to them
+    /// twice.
+    fn submit(&mut self, mv: Move) -> Result<Move, MoveError> {
+        self.make(mv)?;
+        self.undone.clear();
+        if let Some(clock) = &mut self.clock {
+            clock.finish_move(mv.player());
+        }
+        Ok(mv)
+    }
+
+    /// The guts of `submit`, without touching the moves that can be redone.
+    fn make(&mut self, mv: Move) -> Result<(), MoveError> {
+        if self.is_over() {
+            return Err(MoveError::GameOver);
+        }
+        if mv.player() != self.turn {
+            return Err(MoveError::WrongTurn);
+        }
+        if !self.rules.allows(mv) {
+            return Err(MoveError::NotAllowed);
+        }
+
+        let next_turn = self.turn.opponent();
+        let mut placed = self.placed.clone();
+        let board = match mv {
+            Move::Place { pos, player, glyph } => {
+                if self.board.get(pos)?.is_occupied() {
+                    return Err(MoveError::Occupied);
+                }
+                let mut board = self.board;
+                let queue = &mut placed[index(player)];
+                match self.rules.variant {
+                    Variant::Morris { pieces } if queue.len() >= usize::from(pieces) => {
+                        return Err(MoveError::NotAllowed);
+                    }
+                    Variant::Fading { limit } if queue.len() >= usize::from(limit) => {
+                        // Take the oldest one away first, so it can't make a line with the new one.
+                        if let Some(oldest) = queue.pop_front() {
+                            board.set(oldest, Glyph::None);
+                        }
+                    }
+                    _ => {}
+                }
+                queue.push_back(pos);
+                board.set(pos, glyph);
+                board
+            }
+            Move::Rotate { direction, .. } => {
+                let board = Board {
+                    center: self.board.center,
+                    ring: direction.apply(self.board.ring),
+                };
+                // This has to be exact, since every rotation of a board is `==` to it.
+                let repeated = self
+                    .positions
+                    .iter()
+                    .any(|earlier| earlier.turn == next_turn && earlier.board.is_identical(&board));
+                if repeated {
+                    return Err(MoveError::Repetition);
+                }
+                for pos in placed.iter_mut().flatten() {
+                    if let Position::Ring(i) = pos {
+                        *i = direction.map_index(*i, board.ring.len());
+                    }
+                }
+                board
+            }
+            Move::Slide { player, from, to } => {
+                let queue = &mut placed[index(player)];
+                if let Variant::Morris { pieces } = self.rules.variant {
+                    if queue.len() < usize::from(pieces) {
+                        return Err(MoveError::NotAllowed);
+                    }
+                }
+                if self.board.get(from)? != player {
+                    return Err(MoveError::NoPiece);
+                }
+                if self.board.get(to)?.is_occupied() {
+                    return Err(MoveError::Occupied);
+                }
+                if !from.is_adjacent(to, self.board.ring.len()) {
+                    return Err(MoveError::NotAdjacent);
+                }
+
+                let mut board = self.board;
+                board.set(from, Glyph::None);
+                board.set(to, player.glyph());
+                // The glyph's still just as old as it was, so it keeps its place in the queue.
+                if let Some(pos) = queue.iter_mut().find(|pos| **pos == from) {
+                    *pos = to;
+                }
+                board
+            }
+        };
+
+        self.positions.push(Snapshot {
+            board: self.board,
+            turn: self.turn,
+            placed: mem::replace(&mut self.placed, placed),
+        });
+        self.board = board;
+        self.turn = next_turn;
+        self.history.push(mv);
+
+        // Every line goes around with the ring, so a rotation can't actually finish the game, but it's simplest to
+        // check after every move anyway.
+        self.emit(GameEvent::MovePlayed(mv));
+        match self.status() {
+            GameStatus::InProgress => {}
+            GameStatus::Won(player) => self.emit(GameEvent::Won(player)),
+            GameStatus::Draw => self.emit(GameEvent::Drawn),
+        }
+
+        Ok(())
+    }
+
+    /// Whether whoever's turn it is has anything they can slide anywhere.
+    fn can_slide(&self) -> bool {
+        let ring_len = self.board.ring.len();
+        let empty: Vec<Position> = self
+            .board
+            .cells()
+            .filter(|(_, glyph)| glyph.is_empty())
+            // When the center's turned off it's always empty, but it still can't be slid into.
+            .filter(|&(pos, _)| pos != Position::Center || self.rules.center_enabled)
+            .map(|(pos, _)| pos)
+            .collect();
+        self.board
+            .cells()
+            .filter(|&(_, glyph)| glyph == self.turn)
+            .any(|(from, _)| empty.iter().any(|&to| from.is_adjacent(to, ring_len)))
+    }
+
+    fn emit(&mut self, event: GameEvent) {
+        for listener in &mut self.listeners {
+            listener(event);
+        }
+    }
+}
+
+/// The listeners are left out of the clone, since a closure can't be cloned; it starts out with none registered.
+impl Clone for Game {
+    fn clone(&self) -> Self {
+        Self {
+            rules: self.rules,
+            board: self.board,
+            turn: self.turn,
+            placed: self.placed.clone(),
+            history: self.history.clone(),
+            positions: self.positions.clone(),
+            undone: self.undone.clone(),
+            listeners: Vec::new(),
+            clock: self.clock,
+        }
+    }
+}
+
+impl Debug for Game {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Game")
+            .field("rules", &self.rules)
+            .field("board", &self.board)
+            .field("turn", &self.turn)
+            .field("placed", &self.placed)
+            .field("history", &self.history)
+            .field("positions", &self.positions)
+            .field("undone", &self.undone)
+            .field("listeners", &self.listeners.len())
+            .field("clock", &self.clock)
+            .finish()
+    }
+}
+
+impl Default for Game {
+    fn default() -> Self {
+        Self::new(RuleSet::default())
+    }
+}
+
+/// Everything that a move can change, from before it was made.
+#[derive(Debug, Clone)]
+struct Snapshot {
+    board: Board,
+    turn: Player,
+    placed: [VecDeque<Position>; 2],
+}
+
+/// Where `player`'s things go in the arrays in `Game` which have one for each player.
+pub(crate) fn index(player: Player) -> usize {
+    match player {
+        Player::X => 0,
+        Player::O => 1,
+    }
+}
@@ -0,0 +1,72 @@
+//! The maths for laying a board out as a circle, for frontends to draw it with and work out what's been clicked on.
+//!
+//! Angles are in radians, going the same way as `f32::atan2`, from the middle of the board. `rotation` is how far the
+//! whole ring has been turned, which is the angle that cell 0 ends up at. Points are relative to the middle of the board.
+
+use std::f32::consts::PI;
+use std::f32::consts::TAU;
+
+use crate::Position;
+
+/// Get the angle of the middle of cell `i` on a ring with `cells` cells.
+pub fn index_to_angle(i: u8, cells: u8, rotation: f32) -> f32 {
+    rotation + f32::from(i) / f32::from(cells) * TAU
+}
+
+/// Figure out which cell of a ring with `cells` cells is at `angle`.
+pub fn angle_to_index(angle: f32, cells: u8, rotation: f32) -> u8 {
+    // Undo the offset of the ring's rotation, and put the angle in the 0..TAU range.
+    let angle = (angle - rotation).rem_euclid(TAU);
+
+    // `round` gives `cells` for angles just under `TAU`, which is really index 0.
+    (angle / TAU * f32::from(cells)).round() as u8 % cells
+}
+
+/// Get the angles that cell `i` on a ring with `cells` cells starts and ends at.
+///
+/// Cells are centered on `index_to_angle`, so the boundaries are half a cell either side of it.
+pub fn cell_arc(i: u8, cells: u8, rotation: f32) -> (f32, f32) {
+    let middle = index_to_angle(i, cells, rotation);
+    let half = PI / f32::from(cells);
+    (middle - half, middle + half)
+}
+
+/// Get the angle of the point `(x, y)`.
+pub fn point_angle(x: f32, y: f32) -> f32 {
+    y.atan2(x)
+}
+
+/// How big the parts of a drawn board are.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Layout {
+    /// The radius of the circle in the middle.
+    pub center_radius: f32,
+    /// How far the inside edge of the ring is from the middle; anything between this and `center_radius` is the gap
+    /// between the center and the ring.
+    pub ring_inner_radius: f32,
+    /// How far the outside edge of the ring is from the middle.
+    pub ring_outer_radius: f32,
+}
+
+impl Layout {
+    /// Whether the point `(x, y)` is on the ring, regardless of which cell.
+    pub fn on_ring(&self, x: f32, y: f32) -> bool {
+        let dist = x.hypot(y);
+        dist > self.ring_inner_radius && dist < self.ring_outer_radius
+    }
+
+    /// Figure out which cell of a board with `cells` cells in its ring is at `(x, y)`, or `None` if it's not on any.
+    pub fn hit_test(&self, x: f32, y: f32, cells: u8, rotation: f32) -> Option<Position> {
+        if x.hypot(y) < self.center_radius {
+            Some(Position::Center)
+        } else if self.on_ring(x, y) {
+            Some(Position::Ring(angle_to_index(
+                point_angle(x, y),
+                cells,
+                rotation,
+            )))
+        } else {
+            None
+        }
+    }
+}
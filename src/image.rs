@@ -0,0 +1,159 @@
+//! Drawing boards to PNGs without a window, for `Board::render_png`.
+//!
+//! This is laid out the same way as the GUI, just scaled to fit the image.
+
+use tiny_skia::Color;
+use tiny_skia::FillRule;
+use tiny_skia::Paint;
+use tiny_skia::PathBuilder;
+use tiny_skia::Pixmap;
+use tiny_skia::Stroke;
+use tiny_skia::Transform;
+
+use crate::geometry;
+use crate::Board;
+use crate::Glyph;
+use crate::Position;
+
+// These are the GUI's colours.
+const BACKGROUND: Color = Color::BLACK;
+const SURFACE: (u8, u8, u8) = (0, 228, 48);
+const GLYPH: (u8, u8, u8) = (255, 255, 255);
+const WIN_LINE: (u8, u8, u8) = (230, 41, 55);
+
+/// How many straight lines each arc gets approximated with.
+const ARC_STEPS: u16 = 16;
+
+pub(crate) fn render_png(board: &Board, width: u32, height: u32) -> Vec<u8> {
+    let mut pixmap = Pixmap::new(width, height).expect("image must be at least 1x1");
+    pixmap.fill(BACKGROUND);
+
+    // All the sizes are in proportion to the GUI's, where the ring's radius is 300.
+    let radius = width.min(height) as f32 * 0.45;
+    let center_radius = radius / 3.0;
+    let gap = radius / 60.0;
+    let line_thickness = radius / 75.0;
+    let cx = width as f32 / 2.0;
+    let cy = height as f32 / 2.0;
+
+    let cells = board.ring.len();
+    let glyph_distance = (center_radius + radius) / 2.0;
+    let glyph_radius = f32::min(
+        glyph_distance * (std::f32::consts::PI / f32::from(cells)) - gap * 2.0,
+        center_radius * 2.0 / 3.0,
+    )
+    .max(1.0);
+
+    // Where the middle of each cell is.
+    let spot = |pos: Position| match pos {
+        Position::Center => (cx, cy),
+        Position::Ring(i) => {
+            let angle = geometry::index_to_angle(i, cells, 0.0);
+            (
+                cx + glyph_distance * angle.cos(),
+                cy + glyph_distance * angle.sin(),
+            )
+        }
+    };
+
+    let surface = paint(SURFACE);
+    if let Some(circle) = PathBuilder::from_circle(cx, cy, center_radius) {
+        pixmap.fill_path(
+            &circle,
+            &surface,
+            FillRule::Winding,
+            Transform::identity(),
+            None,
+        );
+    }
+
+    for i in 0..cells {
+        let (start, end) = geometry::cell_arc(i, cells, 0.0);
+        let inner = center_radius + gap;
+
+        // Go along the outside edge of the cell and then back along the inside, leaving a gap either side.
+        let mut path = PathBuilder::new();
+        for step in 0..=ARC_STEPS {
+            let portion = f32::from(step) / f32::from(ARC_STEPS);
+            let angle = start + gap / radius + (end - start - 2.0 * gap / radius) * portion;
+            let (x, y) = (cx + radius * angle.cos(), cy + radius * angle.sin());
+            if step == 0 {
+                path.move_to(x, y);
+            } else {
+                path.line_to(x, y);
+            }
+        }
+        for step in (0..=ARC_STEPS).rev() {
+            let portion = f32::from(step) / f32::from(ARC_STEPS);
+            let angle = start + gap / inner + (end - start - 2.0 * gap / inner) * portion;
+            path.line_to(cx + inner * angle.cos(), cy + inner * angle.sin());
+        }
+        path.close();
+
+        if let Some(path) = path.finish() {
+            pixmap.fill_path(
+                &path,
+                &surface,
+                FillRule::Winding,
+                Transform::identity(),
+                None,
+            );
+        }
+    }
+
+    let glyph_paint = paint(GLYPH);
+    let stroke = Stroke {
+        width: line_thickness,
+        ..Stroke::default()
+    };
+    for (pos, glyph) in board.cells() {
+        let (x, y) = spot(pos);
+        let path = match glyph {
+            Glyph::None => continue,
+            Glyph::X => {
+                let off = glyph_radius * std::f32::consts::FRAC_1_SQRT_2;
+                let mut path = PathBuilder::new();
+                path.move_to(x - off, y - off);
+                path.line_to(x + off, y + off);
+                path.move_to(x + off, y - off);
+                path.line_to(x - off, y + off);
+                path.finish()
+            }
+            Glyph::O => PathBuilder::from_circle(x, y, glyph_radius),
+        };
+        if let Some(path) = path {
+            pixmap.stroke_path(&path, &glyph_paint, &stroke, Transform::identity(), None);
+        }
+    }
+
+    let win_paint = paint(WIN_LINE);
+    let win_stroke = Stroke {
+        width: line_thickness * 2.0,
+        ..Stroke::default()
+    };
+    for win in board.wins_merged() {
+        let mut path = PathBuilder::new();
+        for (i, pos) in win.cells(cells).enumerate() {
+            let (x, y) = spot(pos);
+            if i == 0 {
+                path.move_to(x, y);
+            } else {
+                path.line_to(x, y);
+            }
+        }
+        if let Some(path) = path.finish() {
+            pixmap.stroke_path(&path, &win_paint, &win_stroke, Transform::identity(), None);
+        }
+    }
+
+    pixmap
+        .encode_png()
+        .expect("encoding a PNG into memory shouldn't fail")
+}
+
+fn paint((r, g, b): (u8, u8, u8)) -> Paint<'static> {
+    let mut paint = Paint::default();
+    paint.set_color_rgba8(r, g, b, 255);
+    paint.anti_alias = true;
+    paint
+}
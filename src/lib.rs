@@ -1,255 +1,2420 @@
-use std::convert::TryInto;
-use std::fmt;
-use std::fmt::Debug;
-use std::fmt::Display;
-use std::fmt::Formatter;
-use std::fmt::Write;
-use std::hash::Hash;
-use std::hash::Hasher;
-use std::iter::FromIterator;
-use std::ops::Shl;
-use std::ops::Shr;
-
-#[cfg(test)]
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+use alloc::collections::BTreeSet;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::convert::TryFrom;
+use core::fmt;
+use core::fmt::Debug;
+use core::fmt::Display;
+use core::fmt::Formatter;
+use core::fmt::Write;
+use core::hash::Hash;
+use core::hash::Hasher;
+use core::iter;
+use core::iter::FromIterator;
+use core::iter::FusedIterator;
+use core::ops::Shl;
+use core::ops::ShlAssign;
+use core::ops::Shr;
+use core::ops::ShrAssign;
+use core::str::FromStr;
+#[cfg(feature = "std")]
+use std::error::Error;
+#[cfg(feature = "std")]
+use std::sync::OnceLock;
+
+#[cfg(feature = "std")]
+use crate::solver::Solver;
+
+pub mod clock;
+pub mod enumerate;
+pub mod game;
+// This needs floating-point maths like `sin`, which only `std` has.
+#[cfg(feature = "std")]
+pub mod geometry;
+#[cfg(feature = "image")]
+mod image;
+mod macros;
+pub mod multiplayer;
+pub mod multiring;
+#[cfg(feature = "net")]
+pub mod net;
+pub mod rules;
+#[cfg(feature = "std")]
+pub mod selfplay;
+#[cfg(feature = "std")]
+pub mod solver;
+#[cfg(feature = "proptest")]
+pub mod strategies;
+pub mod ultimate;
+pub mod zobrist;
+
+// The tests use the solver and the rest of the `std`-only parts all over the place.
+#[cfg(all(test, feature = "std"))]
 mod tests;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum Glyph {
-    None,
-    X,
-    O,
-}
+/// Glyphs are ordered the same way as their digits in `Ring::to_base3`: `None`, then `X`, then `O`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Glyph {
+    None,
+    X,
+    O,
+}
+
+impl Glyph {
+    /// Whether this is `Glyph::None`, i.e. a cell containing this is empty.
+    pub fn is_empty(self) -> bool {
+        self == Self::None
+    }
+
+    /// Whether this is an X or an O, i.e. a cell containing this has been played in.
+    pub fn is_occupied(self) -> bool {
+        self != Self::None
+    }
+
+    /// Get the glyph of the other player, or `None` for `None`.
+    #[deprecated(
+        note = "use `Player::opponent` instead, which doesn't have to deal with `Glyph::None`"
+    )]
+    pub fn opponent(self) -> Self {
+        match self {
+            Self::None => Self::None,
+            Self::X => Self::O,
+            Self::O => Self::X,
+        }
+    }
+
+    /// Get the player whose glyph this is, or `None` for `Glyph::None`.
+    pub fn player(self) -> Option<Player> {
+        match self {
+            Self::None => None,
+            Self::X => Some(Player::X),
+            Self::O => Some(Player::O),
+        }
+    }
+
+    /// Get the character used for this glyph when displaying rings and boards: `X`, `O`, or a space for `None`.
+    ///
+    /// This is also what `Glyph`'s own `Display` implementation writes.
+    pub fn to_char(self) -> char {
+        match self {
+            Self::None => ' ',
+            Self::X => 'X',
+            Self::O => 'O',
+        }
+    }
+
+    /// The inverse of `to_char`, or `None` if `c` isn't one of the characters it returns.
+    pub fn from_char(c: char) -> Option<Self> {
+        match c {
+            ' ' => Some(Self::None),
+            'X' => Some(Self::X),
+            'O' => Some(Self::O),
+            _ => None,
+        }
+    }
+
+    const fn from_digit(digit: u128) -> Self {
+        match digit {
+            0 => Self::None,
+            1 => Self::X,
+            2 => Self::O,
+            _ => unreachable!(),
+        }
+    }
+}
+
+/// The old name for `Glyph`, from before the crate settled on one name for it.
+#[deprecated(note = "use `Glyph` instead")]
+pub type Cell = Glyph;
+
+impl Display for Glyph {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_char())
+    }
+}
+
+/// One of the two players.
+///
+/// Unlike `Glyph`, this can't be empty, so it's what gets used wherever there has to be a player, like whose turn it
+/// is or who's won. It converts into a `Glyph` (and back with `Glyph::player`), and compares equal to the glyph it
+/// puts down.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Player {
+    X,
+    O,
+}
+
+impl Player {
+    /// Get the other player.
+    pub fn opponent(self) -> Self {
+        match self {
+            Self::X => Self::O,
+            Self::O => Self::X,
+        }
+    }
+
+    /// Get the glyph this player puts down.
+    pub fn glyph(self) -> Glyph {
+        match self {
+            Self::X => Glyph::X,
+            Self::O => Glyph::O,
+        }
+    }
+}
+
+impl From<Player> for Glyph {
+    fn from(player: Player) -> Self {
+        player.glyph()
+    }
+}
+
+/// `None` becomes `Glyph::None`.
+impl From<Option<Player>> for Glyph {
+    fn from(player: Option<Player>) -> Self {
+        player.map_or(Glyph::None, Player::glyph)
+    }
+}
+
+impl PartialEq<Player> for Glyph {
+    fn eq(&self, other: &Player) -> bool {
+        *self == other.glyph()
+    }
+}
+
+impl PartialEq<Glyph> for Player {
+    fn eq(&self, other: &Glyph) -> bool {
+        self.glyph() == *other
+    }
+}
+
+impl Display for Player {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.glyph())
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for Glyph {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        u.choose(&[Self::None, Self::X, Self::O]).copied()
+    }
+
+    fn size_hint(depth: usize) -> (usize, Option<usize>) {
+        u8::size_hint(depth)
+    }
+}
+
+/// A line of three glyphs that somebody's won with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Win {
+    /// A win which is entirely located along the ring.
+    Ring {
+        /// The index in the ring at which this win starts; the `length - 1` cells after it are also part of the win.
+        index: u8,
+        /// How many cells in a row are part of the win. This is always 3, except for the wins from
+        /// `Board::wins_merged`, which can be longer.
+        length: u8,
+        /// Who got three in a row.
+        winner: Player,
+    },
+    /// A win which goes through the center.
+    Center {
+        /// The index of one of the cells on the ring which forms this win; the other one is on the opposite side of the ring.
+        index: u8,
+        /// Who got three in a row.
+        winner: Player,
+    },
+}
+
+impl Win {
+    /// Who got three in a row.
+    pub fn winner(self) -> Player {
+        match self {
+            Self::Ring { winner, .. } | Self::Center { winner, .. } => winner,
+        }
+    }
+
+    /// Get the line that this win is along.
+    ///
+    /// For a ring win that's longer than 3 cells, this is only the first three of them.
+    pub fn line(self) -> Line {
+        match self {
+            Self::Ring { index, .. } => Line::Ring { index },
+            Self::Center { index, .. } => Line::Center { index },
+        }
+    }
+
+    /// Get the cells which make up the win, on a ring with `ring_len` cells.
+    pub fn cells(&self, ring_len: u8) -> impl Iterator<Item = Position> {
+        let win = *self;
+        let count = match win {
+            Self::Ring { length, .. } => length,
+            Self::Center { .. } => 3,
+        };
+        (0..count).map(move |i| match win {
+            Self::Ring { index, .. } => Position::Ring((index + i) % ring_len),
+            Self::Center { index, .. } => match i {
+                0 => Position::Ring(index),
+                1 => Position::Center,
+                _ => Position::Ring((index + ring_len / 2) % ring_len),
+            },
+        })
+    }
+}
+
+/// One of the symmetries of a ring: a rotation or a reflection.
+///
+/// On a ring of `n` cells there are `n` of each, which together make up the dihedral group of order `2n`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Dihedral {
+    /// Move every cell this many places forwards around the ring, so the cell at index `i` ends up at `i + k`.
+    Rotate(u8),
+    /// Flip the ring over, so the cell at index `i` ends up at `k - i`.
+    ///
+    /// When `k` is even the axis goes through cell `k / 2`, and when it's odd it goes between two cells.
+    Reflect(u8),
+}
+
+impl Dihedral {
+    /// Get every symmetry of a ring with `cells` cells.
+    pub fn all(cells: u8) -> impl Iterator<Item = Self> {
+        (0..cells)
+            .map(Self::Rotate)
+            .chain((0..cells).map(Self::Reflect))
+    }
+
+    /// Get the index that the cell at index `i` ends up at, on a ring with `cells` cells.
+    pub fn map_index(self, i: u8, cells: u8) -> u8 {
+        // Reduce everything first, so that none of this can overflow.
+        let i = i % cells;
+        match self {
+            Self::Rotate(k) => (i + k % cells) % cells,
+            Self::Reflect(k) => (k % cells + cells - i) % cells,
+        }
+    }
+
+    /// Apply this symmetry to `ring`. This is the same as `ring.transform(self)`.
+    pub fn apply(self, ring: Ring) -> Ring {
+        ring.transform(self)
+    }
+
+    /// Get the symmetry which undoes this one, on a ring with `cells` cells.
+    pub fn invert(self, cells: u8) -> Self {
+        match self {
+            Self::Rotate(k) => Self::Rotate((cells - k % cells) % cells),
+            // Reflecting twice puts everything back where it started.
+            Self::Reflect(k) => Self::Reflect(k),
+        }
+    }
+}
+
+/// A line of three cells which wins the game when one player fills it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Line {
+    /// Three cells in a row around the ring, starting at `index` and wrapping around the end of the ring.
+    Ring { index: u8 },
+    /// The center and the two cells on either side of it, at `index` and directly opposite `index`.
+    ///
+    /// When the ring has an odd number of cells nothing's directly opposite, so the line goes from `index` to the cell
+    /// `ring_len / 2` (rounded down) further around instead. That's one of the two cells either side of where the
+    /// opposite cell would be, and the other one gets its own line starting from the other end.
+    Center { index: u8 },
+}
+
+impl Line {
+    /// Get every line on a ring with `ring_len` cells: the ones around the ring in order, followed by the ones through
+    /// the center.
+    pub fn all(ring_len: u8) -> impl Iterator<Item = Self> {
+        (0..Self::ring_count(ring_len))
+            .map(|index| Self::Ring { index })
+            .chain((0..Self::center_count(ring_len)).map(|index| Self::Center { index }))
+    }
+
+    /// How many lines there are around a ring with `ring_len` cells.
+    ///
+    /// Rings with fewer than 3 cells don't have any, since the line would have to wrap around onto the same cell twice.
+    fn ring_count(ring_len: u8) -> u8 {
+        if ring_len >= 3 {
+            ring_len
+        } else {
+            0
+        }
+    }
+
+    /// How many lines there are through the center of a ring with `ring_len` cells.
+    ///
+    /// With an even number of cells, the lines from `index` and the cell opposite it are the same line,
+    /// so there are only half as many of them as there are cells. A ring with only one cell doesn't have any, since
+    /// both ends of the line would be the same cell.
+    fn center_count(ring_len: u8) -> u8 {
+        if ring_len == 1 {
+            0
+        } else if ring_len.is_multiple_of(2) {
+            ring_len / 2
+        } else {
+            ring_len
+        }
+    }
+
+    /// Get the cells which make up the line, on a ring with `ring_len` cells.
+    pub fn cells(self, ring_len: u8) -> [Position; 3] {
+        match self {
+            Line::Ring { index } => [
+                Position::Ring(index),
+                Position::Ring((index + 1) % ring_len),
+                Position::Ring((index + 2) % ring_len),
+            ],
+            Line::Center { index } => [
+                Position::Ring(index),
+                Position::Center,
+                Position::Ring((index + ring_len / 2) % ring_len),
+            ],
+        }
+    }
+}
+
+/// A place on the board where a glyph can be put.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Position {
+    /// The center.
+    Center,
+    /// This index in the ring.
+    Ring(u8),
+}
+
+impl Position {
+    /// Whether this position and `other` are next to each other, on a board with `ring_len` cells in its ring.
+    ///
+    /// Ring cells are next to the ones either side of them around the ring, and the center is next to all of them.
+    pub fn is_adjacent(self, other: Position, ring_len: u8) -> bool {
+        match (self, other) {
+            (Position::Center, Position::Center) => false,
+            (Position::Center, Position::Ring(_)) | (Position::Ring(_), Position::Center) => true,
+            (Position::Ring(a), Position::Ring(b)) => {
+                a != b && ((a + 1) % ring_len == b || (b + 1) % ring_len == a)
+            }
+        }
+    }
+}
+
+/// Something a player can do on their turn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Move {
+    /// Have `player` put `glyph` at `pos`. This is the only kind of move in the normal game.
+    ///
+    /// Normally `glyph` has to be `player`'s own glyph; only `rules::Variant::OrderAndChaos` lets players put down
+    /// either one.
+    Place {
+        pos: Position,
+        player: Player,
+        glyph: Glyph,
+    },
+    /// Spend the turn turning the ring one cell in `direction` instead, which only `rules::Variant::Rotation` allows.
+    Rotate {
+        player: Player,
+        direction: Direction,
+    },
+    /// Move one of `player`'s glyphs from `from` to the empty cell `to` next to it, which only `rules::Variant::Morris`
+    /// allows.
+    Slide {
+        player: Player,
+        from: Position,
+        to: Position,
+    },
+}
+
+impl Move {
+    /// Have `player` put their own glyph at `pos`.
+    pub fn place(pos: Position, player: Player) -> Self {
+        Self::Place {
+            pos,
+            player,
+            glyph: player.glyph(),
+        }
+    }
+
+    /// Who's making the move.
+    pub fn player(self) -> Player {
+        match self {
+            Self::Place { player, .. }
+            | Self::Rotate { player, .. }
+            | Self::Slide { player, .. } => player,
+        }
+    }
+
+    /// Where the move puts a glyph, or `None` if it doesn't put one anywhere.
+    pub fn pos(self) -> Option<Position> {
+        match self {
+            Self::Place { pos, .. } => Some(pos),
+            Self::Slide { to, .. } => Some(to),
+            Self::Rotate { .. } => None,
+        }
+    }
+}
+
+/// A way the ring can be turned, for `Move::Rotate`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Direction {
+    /// Forwards, so the glyph at index `i` ends up at `i + 1`. The ring goes clockwise, the same way `geometry` lays it
+    /// out.
+    Clockwise,
+    /// Backwards, so the glyph at index `i` ends up at `i - 1`.
+    Anticlockwise,
+}
+
+impl Direction {
+    /// Get the direction which undoes this one.
+    pub fn reverse(self) -> Self {
+        match self {
+            Self::Clockwise => Self::Anticlockwise,
+            Self::Anticlockwise => Self::Clockwise,
+        }
+    }
+
+    /// Turn `ring` one cell in this direction.
+    pub fn apply(self, ring: Ring) -> Ring {
+        match self {
+            Self::Clockwise => ring.rotate_right(1),
+            Self::Anticlockwise => ring.rotate_left(1),
+        }
+    }
+
+    /// Get the index that the cell at index `i` ends up at after turning a ring with `cells` cells this way.
+    pub fn map_index(self, i: u8, cells: u8) -> u8 {
+        match self {
+            Self::Clockwise => (i + 1) % cells,
+            Self::Anticlockwise => (i + cells - 1) % cells,
+        }
+    }
+}
+
+/// The reasons why `Board::play` or `Board::apply` might refuse a move.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MoveError {
+    /// There's already a glyph in that cell.
+    Occupied,
+    /// There isn't one of the player's glyphs to move in that cell.
+    NoPiece,
+    /// The cells aren't next to each other, so a glyph can't slide between them.
+    NotAdjacent,
+    /// The index is past the end of the ring.
+    OutOfRange { index: u8, cells: u8 },
+    /// The game has already finished, so no more moves can be made.
+    GameOver,
+    /// It isn't the turn of the player making the move.
+    WrongTurn,
+    /// The rules of the game don't allow the move, like putting something in the center when it's turned off, or
+    /// rotating the ring in the normal game.
+    NotAllowed,
+    /// The move would make the board exactly the same as it was earlier on in the game, with the same player to move.
+    Repetition,
+    /// In ultimate ring-tac-toe, the move isn't on the small board that has to be played on next, or that board has
+    /// already been decided.
+    WrongBoard,
+}
+
+/// Another name for `MoveError`, to go with `Board::apply`.
+pub type IllegalMove = MoveError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum GameResult {
+    /// Nobody has won yet, and there are still cells left to play in.
+    InProgress,
+    /// Somebody got three in a row.
+    Won(Player),
+    /// The board filled up without anyone winning.
+    Draw,
+}
+
+/// Another name for `GameResult`, to go with `Board::status`.
+pub type GameStatus = GameResult;
+
+impl Display for Win {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Ring { index, .. } => write!(f, "around the ring from index {}", index),
+            Self::Center { index, .. } => write!(f, "through the center from index {}", index),
+        }
+    }
+}
+
+impl Display for GameResult {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InProgress => write!(f, "in progress"),
+            Self::Won(player) => write!(f, "{} won", player),
+            Self::Draw => write!(f, "draw"),
+        }
+    }
+}
+
+/// The reasons why a board might not be able to be built.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BoardError {
+    /// The ring doesn't have any cells.
+    NoCells,
+    /// The ring has more than `Ring::MAX_CELLS` cells, which won't fit in a `Ring`.
+    TooManyCells(u8),
+    /// In a `MultiRing`, the innermost ring has an odd number of cells, so the spokes don't work.
+    OddCells(u8),
+    /// A cell was set at an index past the end of the ring.
+    OutOfRange { index: u8, cells: u8 },
+    /// In a `MultiRing`, the outer ring's cells don't line up with the inner ring's,
+    /// because it isn't a bigger multiple of the inner ring's size.
+    MisalignedRings { inner: u8, outer: u8 },
+    /// The ring given to `BoardBuilder::ring_from_str` couldn't be parsed.
+    Parse(ParseRingError),
+    /// The ring given to `BoardBuilder::ring_from_str` had a different number of cells to the board.
+    WrongLength { expected: u8, found: u8 },
+}
+
+impl BoardError {
+    /// Check that a ring with `cells` cells can be used in a board.
+    fn check_cells(cells: u8) -> Result<(), Self> {
+        if cells == 0 {
+            Err(Self::NoCells)
+        } else if cells > Ring::MAX_CELLS {
+            Err(Self::TooManyCells(cells))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// The reasons why a string might not be able to be parsed into a `Ring` or `Board`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseRingError {
+    /// The string was empty, and a ring needs at least one cell.
+    Empty,
+    /// The string had this many cells, which is more than the `Ring::MAX_CELLS` which fit in a `Ring`.
+    TooLong(usize),
+    /// This character doesn't represent a cell.
+    InvalidChar(char),
+    /// When parsing a `Board`, the string didn't start with a single character for the center followed by a `|`.
+    NoCenter,
+}
+
+/// The reasons why `Board::from_bytes` might not be able to decode a board.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecodeError {
+    /// The bytes ended before the end of the board.
+    TooShort,
+    /// There were more bytes after the end of the board.
+    TooLong,
+    /// The bytes were in a version of the format that this version of the crate doesn't know about.
+    UnknownVersion(u8),
+    /// The board had an invalid number of cells.
+    Board(BoardError),
+    /// The center wasn't a base-3 digit.
+    InvalidCenter(u8),
+    /// The ring's integer was too big for its number of cells.
+    RingOutOfRange,
+    /// This character isn't valid in a code from `Board::to_code`.
+    InvalidChar(char),
+}
+
+/// The reasons why `Board::diff` might not be able to work out the move between two boards.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffError {
+    /// The boards' rings have different numbers of cells.
+    DifferentSizes { before: u8, after: u8 },
+    /// The boards are exactly the same.
+    NoChange,
+    /// This many cells are different between the boards, rather than just one.
+    TooManyChanges(u8),
+    /// The cell at this position wasn't empty before, so a glyph was taken away or replaced rather than put there.
+    NotAPlacement(Position),
+    /// Only one glyph was put down, but it couldn't have been played there.
+    Illegal(MoveError),
+}
+
+/// The digits used by `Board::to_code`, which is the URL-safe base64 alphabet.
+const CODE_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+/// Encode `bytes` in URL-safe base64 without any padding, for `Board::to_code`.
+fn encode_code(bytes: &[u8]) -> String {
+    let mut code = String::new();
+    for chunk in bytes.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, &byte)| n | u32::from(byte) << (16 - 8 * i));
+        // 3 bytes make 4 characters, so a chunk of `len` bytes needs `len + 1` of them.
+        for i in 0..=chunk.len() {
+            let digit = n >> (18 - 6 * i) & 0b111111;
+            code.push(char::from(CODE_ALPHABET[digit as usize]));
+        }
+    }
+    code
+}
+
+/// How many bytes `Board::to_bytes` uses for a ring with `cells` cells.
+fn ring_bytes(cells: u8) -> usize {
+    let max = 3u128.pow(cells.into()) - 1;
+    let bits = 128 - max.leading_zeros();
+    // `bits` is at most 127, so this always fits.
+    bits.div_ceil(8) as usize
+}
+
+impl Display for MoveError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Occupied => write!(f, "that cell is already taken"),
+            Self::NoPiece => write!(f, "there's nothing of yours to move there"),
+            Self::NotAdjacent => write!(f, "those cells aren't next to each other"),
+            Self::OutOfRange { index, cells } => {
+                write!(f, "index {} out of range for {}-cell ring", index, cells)
+            }
+            Self::GameOver => write!(f, "the game is already over"),
+            Self::WrongTurn => write!(f, "it's the other player's turn"),
+            Self::NotAllowed => write!(f, "the rules don't allow that move"),
+            Self::Repetition => write!(f, "that would repeat an earlier position"),
+            Self::WrongBoard => write!(f, "that board can't be played on right now"),
+        }
+    }
+}
+
+impl Display for BoardError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoCells => write!(f, "a ring needs at least one cell"),
+            Self::TooManyCells(cells) => {
+                write!(
+                    f,
+                    "{} cells is too many; rings can have at most {}",
+                    cells,
+                    Ring::MAX_CELLS
+                )
+            }
+            Self::OddCells(cells) => write!(
+                f,
+                "rings need an even number of cells for lines through the center, but this one has {}",
+                cells
+            ),
+            Self::OutOfRange { index, cells } => {
+                write!(f, "index {} out of range for {}-cell ring", index, cells)
+            }
+            Self::MisalignedRings { inner, outer } => write!(
+                f,
+                "a {}-cell outer ring doesn't line up with a {}-cell inner ring",
+                outer, inner
+            ),
+            Self::Parse(err) => write!(f, "{}", err),
+            Self::WrongLength { expected, found } => write!(
+                f,
+                "expected {} cells in the ring, found {}",
+                expected, found
+            ),
+        }
+    }
+}
+
+impl Display for ParseRingError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Empty => write!(f, "a ring needs at least one cell"),
+            Self::TooLong(cells) => {
+                write!(
+                    f,
+                    "{} cells is too many; rings can have at most {}",
+                    cells,
+                    Ring::MAX_CELLS
+                )
+            }
+            Self::InvalidChar(c) => write!(f, "{:?} isn't a valid cell", c),
+            Self::NoCenter => write!(f, "expected the center followed by '|'"),
+        }
+    }
+}
+
+impl Display for DiffError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::DifferentSizes { before, after } => {
+                write!(f, "the ring went from {} cells to {} cells", before, after)
+            }
+            Self::NoChange => write!(f, "the boards are the same"),
+            Self::TooManyChanges(changes) => {
+                write!(f, "{} cells changed, rather than just one", changes)
+            }
+            Self::NotAPlacement(pos) => write!(f, "the glyph at {:?} was changed", pos),
+            Self::Illegal(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl Display for DecodeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::TooShort => write!(f, "not enough bytes for a board"),
+            Self::TooLong => write!(f, "extra bytes after the end of the board"),
+            Self::UnknownVersion(version) => write!(f, "unknown encoding version {}", version),
+            Self::Board(err) => write!(f, "{}", err),
+            Self::InvalidCenter(center) => write!(f, "{} isn't a valid center", center),
+            Self::RingOutOfRange => write!(f, "the ring's integer is too big for its size"),
+            Self::InvalidChar(c) => write!(f, "{:?} isn't valid in a position code", c),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl Error for MoveError {}
+
+#[cfg(feature = "std")]
+impl Error for DecodeError {}
+
+#[cfg(feature = "std")]
+impl Error for DiffError {}
+
+#[cfg(feature = "std")]
+impl Error for BoardError {}
+
+#[cfg(feature = "std")]
+impl Error for ParseRingError {}
+
+#[derive(Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Board {
+    /// Assigning to this skips all the checks that `play` does, so prefer `play(Position::Center)` for making moves.
+    pub center: Glyph,
+    pub ring: Ring,
+}
+
+impl Board {
+    /// The version of the format that `to_bytes` produces, which is its first byte.
+    pub const ENCODING_VERSION: u8 = 1;
+
+    /// The number of cells in the ring of a `Board::default()`, which is how many the original game had.
+    pub const DEFAULT_CELLS: u8 = 8;
+
+    /// Create a new, blank board with `cells` around the outside.
+    ///
+    /// Panics if the board can't have that many cells; see `try_new` for the reasons why.
+    pub fn new(cells: u8) -> Self {
+        Self::try_new(cells).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Create a new, blank board with `cells` around the outside,
+    /// or an error if there aren't any cells or there are more than `Ring::MAX_CELLS`.
+    pub fn try_new(cells: u8) -> Result<Self, BoardError> {
+        BoardError::check_cells(cells)?;
+        Ok(Self {
+            center: Glyph::None,
+            ring: Ring::new(cells),
+        })
+    }
+
+    /// Get whoever's won the game, or `None` if nobody has yet.
+    pub fn winner(&self) -> Option<Player> {
+        self.wins_iter().next().map(Win::winner)
+    }
+
+    /// Get all of the ways in which the game has been won.
+    pub fn wins(&self) -> Vec<Win> {
+        self.wins_iter().collect()
+    }
+
+    /// Like `wins`, but working them out one at a time as they're needed, without allocating.
+    ///
+    /// The ring wins come first, in order of where they start, followed by the ones through the center.
+    pub fn wins_iter(&self) -> impl Iterator<Item = Win> {
+        let ring = self.ring;
+        let len = ring.len();
+        let ring_wins = (0..Line::ring_count(len)).filter_map(move |index| {
+            let winner = ring.get(index).player()?;
+            // `get` wraps around, so this picks up the wins over the end of the ring too.
+            let won =
+                ring.get((index + 1) % len) == winner && ring.get((index + 2) % len) == winner;
+            won.then_some(Win::Ring {
+                index,
+                length: 3,
+                winner,
+            })
+        });
+
+        ring_wins.chain(self.center_wins())
+    }
+
+    /// Like `wins`, but when there are more than 3 of the same glyph in a row around the ring, the overlapping wins
+    /// along them get merged into one long win covering the whole run.
+    pub fn wins_merged(&self) -> Vec<Win> {
+        let mut out = Vec::new();
+        let len = self.ring.len();
+
+        if self.ring.iter().all(|glyph| glyph == self.ring.get(0)) {
+            // There's no start to a run that goes all the way around, so just say it starts at 0. It still has to be at
+            // least 3 long, though, or else it's only a line because it wraps around onto the same cells.
+            if let Some(winner) = self.ring.get(0).player().filter(|_| len >= 3) {
+                out.push(Win::Ring {
+                    index: 0,
+                    length: len,
+                    winner,
+                });
+            }
+        } else {
+            for index in 0..len {
+                let glyph = self.ring.get(index);
+                // Only look at the cells where runs start.
+                let winner = match glyph.player() {
+                    Some(winner) if self.ring.get((index + len - 1) % len) != glyph => winner,
+                    _ => continue,
+                };
+
+                let length = (0..len)
+                    .take_while(|&i| self.ring.get((index + i) % len) == glyph)
+                    .count() as u8;
+                if length >= 3 {
+                    out.push(Win::Ring {
+                        index,
+                        length,
+                        winner,
+                    });
+                }
+            }
+        }
+
+        out.extend(self.center_wins());
+
+        out
+    }
+
+    /// Get all the wins through the center.
+    fn center_wins(&self) -> impl Iterator<Item = Win> {
+        let board = *self;
+        // If the middle is blank, there can't be a win through the middle, so this is empty.
+        board.center.player().into_iter().flat_map(move |winner| {
+            (0..Line::center_count(board.ring.len()))
+                .filter(move |&index| {
+                    board.line_glyphs(Line::Center { index }) == [winner.glyph(); 3]
+                })
+                .map(move |index| Win::Center { index, winner })
+        })
+    }
+
+    /// Get a description of the board, its result and all of its wins, for debugging.
+    ///
+    /// ```text
+    /// center: [X]
+    /// ring:   [XXX O O ]
+    /// result: X won
+    /// win:    around the ring from index 0
+    /// ```
+    pub fn describe(&self) -> String {
+        let mut out = format!(
+            "center: [{}]\nring:   [{}]\nresult: {}\n",
+            self.center.to_char(),
+            self.ring,
+            self.result()
+        );
+        for win in self.wins() {
+            writeln!(out, "win:    {}", win).unwrap();
+        }
+        out
+    }
+
+    /// Draw the board as an actual circle of characters, with the center in the middle and `.` for empty cells.
+    ///
+    /// The ring starts at the right and goes clockwise, the same way `geometry` lays it out. Columns are twice as far
+    /// apart as rows so that it comes out roughly round in a terminal, and the circle gets bigger as needed to fit
+    /// every cell in. Here's `X|XO   O X`:
+    ///
+    /// ```text
+    ///     .
+    ///  O     X
+    /// .   X   X
+    ///  .     O
+    ///     .
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn render_ascii(&self) -> String {
+        self.render_circle(|_, glyph| match glyph {
+            Glyph::None => ".".to_owned(),
+            glyph => glyph.to_char().to_string(),
+        })
+    }
+
+    /// Like `render_ascii`, but with ANSI colour codes for terminals: X is red, O is blue, and the cells of any wins are
+    /// highlighted.
+    #[cfg(feature = "ansi")]
+    pub fn render_ansi(&self) -> String {
+        let winning: Vec<_> = self
+            .wins_iter()
+            .flat_map(|win| win.cells(self.ring.len()))
+            .collect();
+
+        self.render_circle(|pos, glyph| {
+            let (color, c) = match glyph {
+                // Dim the empty cells, so that they don't draw attention away from the glyphs.
+                Glyph::None => ("2", '.'),
+                Glyph::X => ("31", 'X'),
+                Glyph::O => ("34", 'O'),
+            };
+            if winning.contains(&pos) {
+                // Bold and inverted, so the whole cell lights up in the glyph's colour.
+                format!("\x1b[1;7;{}m{}\x1b[0m", color, c)
+            } else {
+                format!("\x1b[{}m{}\x1b[0m", color, c)
+            }
+        })
+    }
+
+    /// Lay the board out as a circle of text, like `render_ascii` does, using `draw` to get the text for each cell.
+    #[cfg(feature = "std")]
+    fn render_circle(&self, draw: impl Fn(Position, Glyph) -> String) -> String {
+        let cells = self.ring.len();
+
+        // Find the smallest circle where no two cells land on the same character. Anything smaller than 2 squashes the
+        // ring right up against the center.
+        let (radius, spots) = (2..)
+            .map(|radius: usize| {
+                let spots: Vec<_> = (0..cells)
+                    .map(|i| {
+                        let angle = geometry::index_to_angle(i, cells, 0.0);
+                        let r = radius as f32;
+                        let row = (r + r * angle.sin()).round() as usize;
+                        let col = (2.0 * r + 2.0 * r * angle.cos()).round() as usize;
+                        (row, col)
+                    })
+                    .collect();
+                (radius, spots)
+            })
+            .find(|(radius, spots)| {
+                let mut seen = vec![(*radius, 2 * radius)];
+                spots.iter().all(|spot| {
+                    let new = !seen.contains(spot);
+                    seen.push(*spot);
+                    new
+                })
+            })
+            .unwrap();
+
+        let mut grid = vec![vec![" ".to_owned(); 4 * radius + 1]; 2 * radius + 1];
+        grid[radius][2 * radius] = draw(Position::Center, self.center);
+        for (i, ((row, col), glyph)) in spots.into_iter().zip(&self.ring).enumerate() {
+            grid[row][col] = draw(Position::Ring(i as u8), glyph);
+        }
+
+        let mut out = String::new();
+        for row in grid {
+            out.push_str(row.concat().trim_end());
+            out.push('\n');
+        }
+        out
+    }
+
+    /// Draw the board the same way as the GUI does, into a PNG image `width` by `height` pixels big.
+    ///
+    /// This doesn't need a window or a GPU, so it works for bots and servers which want to post pictures of games.
+    ///
+    /// Panics if `width` or `height` is 0.
+    #[cfg(feature = "image")]
+    pub fn render_png(&self, width: u32, height: u32) -> Vec<u8> {
+        image::render_png(self, width, height)
+    }
+
+    /// Check whether two boards have exactly the same glyphs in exactly the same places.
+    ///
+    /// This is stricter than `==`, which also treats rotations and reflections of the ring as equal.
+    pub fn is_identical(&self, other: &Board) -> bool {
+        self.center == other.center && Literal(self.ring) == Literal(other.ring)
+    }
+
+    /// Empty every cell on the board, keeping the same number of cells in the ring.
+    pub fn clear(&mut self) {
+        self.center = Glyph::None;
+        self.ring.clear();
+    }
+
+    /// Rotate and/or reflect the ring into its canonical form, so that boards which are equal end up identical.
+    /// The center stays where it is, since it isn't affected by any of the symmetries.
+    pub fn canonicalize(self) -> Self {
+        Self {
+            center: self.center,
+            ring: self.ring.canonicalize(),
+        }
+    }
+
+    /// Like `Ring::canonical_index`, but for the whole board: boards which are rotations or reflections of each other
+    /// get the same index, and every index below `Board::canonical_count` belongs to exactly one of them.
+    ///
+    /// Panics if the ring has more than 20 cells.
+    #[cfg(feature = "std")]
+    pub fn canonical_index(&self) -> u32 {
+        let center = match self.center {
+            Glyph::None => 0,
+            Glyph::X => 1,
+            Glyph::O => 2,
+        };
+        self.ring.canonical_index() * 3 + center
+    }
+
+    /// The inverse of `canonical_index`: get the canonical board with `cells` cells in its ring at `index`,
+    /// or `None` if there aren't that many canonical boards.
+    ///
+    /// Panics if `cells` is more than 20.
+    #[cfg(feature = "std")]
+    pub fn from_canonical_index(cells: u8, index: u32) -> Option<Self> {
+        Some(Self {
+            center: Glyph::from_digit((index % 3).into()),
+            ring: Ring::from_canonical_index(cells, index / 3)?,
+        })
+    }
+
+    /// Get how many canonical boards there are with `cells` cells in the ring.
+    ///
+    /// Panics if `cells` is more than 20.
+    #[cfg(feature = "std")]
+    pub fn canonical_count(cells: u8) -> u32 {
+        Ring::canonical_count(cells) * 3
+    }
+
+    /// Apply the symmetry `d` to the ring. The center stays where it is.
+    pub fn transform(self, d: Dihedral) -> Self {
+        Self {
+            center: self.center,
+            ring: self.ring.transform(d),
+        }
+    }
+
+    /// Whose turn it is, based on how many of each glyph are on the board.
+    ///
+    /// X always goes first, so it's X's turn whenever there are as many Xs as Os.
+    pub fn turn(&self) -> Player {
+        let (xs, os) = self.counts();
+        if xs > os {
+            Player::O
+        } else {
+            Player::X
+        }
+    }
+
+    /// Like `turn`, but `None` when nobody's meant to move: either the game is over, or the board isn't valid
+    /// (see `is_valid`) so it's impossible to say.
+    pub fn turn_to_move(&self) -> Option<Player> {
+        if self.is_valid() && self.result() == GameResult::InProgress {
+            Some(self.turn())
+        } else {
+            None
+        }
+    }
+
+    /// Whether this board makes sense: X has the same number of glyphs as O or one more, and they haven't both won.
+    ///
+    /// This can still be true for positions that can't come up in a real game; see `is_reachable` for that.
+    pub fn is_valid(&self) -> bool {
+        let (xs, os) = self.counts();
+        (xs == os || xs == os + 1) && !(self.has_won(Player::X) && self.has_won(Player::O))
+    }
+
+    /// Whether this board can come up in an actual game, which means that it's valid (see `is_valid`) and nobody kept
+    /// playing after the game had already been won.
+    pub fn is_reachable(&self) -> bool {
+        if !self.is_valid() {
+            return false;
+        }
+
+        let winner = match self.winner() {
+            None => return true,
+            Some(winner) => winner,
+        };
+
+        // The winner must've made the last move, which means it's the other player's turn.
+        if self.turn() == winner {
+            return false;
+        }
+
+        // One of the winner's glyphs was the last one put down, and the game wasn't won before then. Wins only ever
+        // get added by putting down glyphs, so that's enough to make sure that every earlier position was fine too.
+        let mut board = *self;
+        self.cells().any(|(pos, glyph)| {
+            if glyph != winner {
+                return false;
+            }
+            board.set(pos, Glyph::None);
+            let won_before = board.winner().is_some();
+            board.set(pos, winner.glyph());
+            !won_before
+        })
+    }
+
+    /// How many Xs and Os there are on the board, including in the center.
+    fn counts(&self) -> (u8, u8) {
+        let mut xs = self.ring.count(Glyph::X);
+        let mut os = self.ring.count(Glyph::O);
+        match self.center {
+            Glyph::X => xs += 1,
+            Glyph::O => os += 1,
+            Glyph::None => {}
+        }
+        (xs, os)
+    }
+
+    /// Whether `player` has three in a row anywhere.
+    fn has_won(&self, player: Player) -> bool {
+        self.lines()
+            .into_iter()
+            .any(|line| self.line_glyphs(line) == [player.glyph(); 3])
+    }
+
+    pub fn result(&self) -> GameResult {
+        match self.winner() {
+            None => {
+                if self.is_full() {
+                    GameResult::Draw
+                } else {
+                    GameResult::InProgress
+                }
+            }
+            Some(player) => GameResult::Won(player),
+        }
+    }
+
+    /// Another name for `result`: whether the game is still going, and if not, how it ended.
+    ///
+    /// Unlike `winner`, this can tell the difference between a game that's still going and a draw.
+    pub fn status(&self) -> GameStatus {
+        self.result()
+    }
+
+    /// Iterate over every cell on the board and what's in it, starting with the center and then going around the ring.
+    pub fn cells(&self) -> impl Iterator<Item = (Position, Glyph)> {
+        let ring = self
+            .ring
+            .iter()
+            .enumerate()
+            .map(|(i, glyph)| (Position::Ring(i as u8), glyph));
+        iter::once((Position::Center, self.center)).chain(ring)
+    }
+
+    /// Whether every cell on the board, including the center, has a glyph in it.
+    pub fn is_full(&self) -> bool {
+        self.center.is_occupied() && self.ring.is_full()
+    }
+
+    /// Get all the moves which can currently be made, or nothing if the game is over.
+    pub fn available_moves(&self) -> Vec<Position> {
+        self.legal_moves(self.turn()).collect()
+    }
+
+    /// Iterate over the positions `player` can currently put their glyph in: the empty ones, center first.
+    ///
+    /// This yields nothing if the game's over or it isn't `player`'s turn.
+    pub fn legal_moves(&self, player: Player) -> impl Iterator<Item = Position> {
+        let playing = player == self.turn() && self.result() == GameResult::InProgress;
+        let center = self.center.is_empty().then_some(Position::Center);
+        let ring = self
+            .ring
+            .iter()
+            .enumerate()
+            .filter(|(_, glyph)| glyph.is_empty())
+            .map(|(i, _)| Position::Ring(i as u8));
+
+        center.into_iter().chain(ring).filter(move |_| playing)
+    }
+
+    /// Like `legal_moves` for whoever's turn it is, but skipping any move which leads to a rotation or reflection of
+    /// a position that an earlier move led to.
+    ///
+    /// Those moves are just as good as each other, so a search only needs to look at one of them. On an empty board,
+    /// this leaves only the center and a single ring cell.
+    pub fn legal_moves_canonical(&self) -> impl Iterator<Item = Position> {
+        let board = *self;
+        let mut seen = BTreeSet::new();
+        self.legal_moves(self.turn())
+            .filter(move |&mv| seen.insert(board.with_move(mv).unwrap()))
+    }
+
+    /// Put the glyph of whoever's turn it is at `mv`.
+    pub fn play(&mut self, mv: Position) -> Result<(), MoveError> {
+        if self.result() != GameResult::InProgress {
+            return Err(MoveError::GameOver);
+        }
+
+        if self.get(mv)?.is_occupied() {
+            return Err(MoveError::Occupied);
+        }
+
+        self.set(mv, self.turn().glyph());
+
+        Ok(())
+    }
+
+    /// Whether `mv` is allowed right now: the game isn't over yet, and the cell is in range and empty.
+    ///
+    /// This is what `play` checks before it makes a move, without making it.
+    pub fn legal(&self, mv: Position) -> bool {
+        self.result() == GameResult::InProgress && self.get(mv).is_ok_and(Glyph::is_empty)
+    }
+
+    /// Like `play`, but for a `Move` which says who's making it, which has to be whoever's turn it is.
+    ///
+    /// A board always follows the normal rules, so this refuses `Move::Rotate`; use a `Game` for the other variants.
+    pub fn apply(&mut self, mv: Move) -> Result<(), IllegalMove> {
+        if self.result() == GameResult::InProgress && mv.player() != self.turn() {
+            return Err(MoveError::WrongTurn);
+        }
+
+        match mv {
+            Move::Place { pos, player, glyph } if glyph == player => self.play(pos),
+            _ => Err(MoveError::NotAllowed),
+        }
+    }
+
+    /// Take back `mv`, emptying the cell that it put a glyph in.
+    ///
+    /// Panics if that cell doesn't have `mv`'s glyph in it, since then `mv` can't have been the move made there, or if
+    /// `mv` isn't a `Move::Place`.
+    pub fn undo(&mut self, mv: Move) {
+        let (pos, glyph) = match mv {
+            Move::Place { pos, glyph, .. } => (pos, glyph),
+            Move::Rotate { .. } | Move::Slide { .. } => panic!("undoing a move that wasn't made"),
+        };
+        let found = self.get(pos).expect("move is out of range");
+        assert_eq!(found, glyph, "undoing a move that wasn't made");
+        self.set(pos, Glyph::None);
+    }
+
+    /// Like `apply`, but also updates `hash` to match, if it's this board's `zobrist` hash.
+    ///
+    /// If the move isn't allowed, neither the board nor `hash` is changed.
+    pub fn apply_zobrist(&mut self, mv: Move, hash: &mut u64) -> Result<(), IllegalMove> {
+        self.apply(mv)?;
+        *hash = zobrist::update(*hash, mv);
+        Ok(())
+    }
+
+    /// Like `undo`, but also updates `hash` to match, if it's this board's `zobrist` hash.
+    pub fn undo_zobrist(&mut self, mv: Move, hash: &mut u64) {
+        self.undo(mv);
+        *hash = zobrist::update(*hash, mv);
+    }
+
+    /// Get this board's Zobrist hash, which can be kept up to date cheaply with `apply_zobrist` and `undo_zobrist`.
+    ///
+    /// Unlike `Hash`, this isn't symmetry-aware: rotations and reflections of a board all get different hashes.
+    pub fn zobrist(&self) -> u64 {
+        zobrist::hash(self)
+    }
+
+    /// Get the Zobrist hash of this board's canonical form, which is the same for all of its rotations and reflections.
+    ///
+    /// This has to canonicalize the board, so it can't be updated incrementally.
+    pub fn zobrist_canonical(&self) -> u64 {
+        self.canonicalize().zobrist()
+    }
+
+    /// Encode the board in a compact binary format, for sending over the network or using as a key in a table.
+    ///
+    /// The format is:
+    /// - 1 byte for the version of the format, which is currently `Board::ENCODING_VERSION`.
+    /// - 1 byte for the number of cells in the ring.
+    /// - 1 byte for the center, as its base-3 digit.
+    /// - The ring's integer (see `Ring::to_base3`) in little-endian, with only as many bytes as the biggest ring of
+    ///   that size needs.
+    ///
+    /// That comes out to 5 bytes for an 8-cell board.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let cells = self.ring.len();
+        let mut bytes = vec![Self::ENCODING_VERSION, cells, self.center as u8];
+        bytes.extend_from_slice(&self.ring.int.to_le_bytes()[..ring_bytes(cells)]);
+        bytes
+    }
+
+    /// The inverse of `to_bytes`.
+    ///
+    /// Everything gets checked, so this is safe to use on bytes that came from somewhere untrusted.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, DecodeError> {
+        let (&version, rest) = bytes.split_first().ok_or(DecodeError::TooShort)?;
+        if version != Self::ENCODING_VERSION {
+            return Err(DecodeError::UnknownVersion(version));
+        }
+        let (&cells, rest) = rest.split_first().ok_or(DecodeError::TooShort)?;
+        BoardError::check_cells(cells).map_err(DecodeError::Board)?;
+        let (&center, rest) = rest.split_first().ok_or(DecodeError::TooShort)?;
+        let center = match center {
+            0..=2 => Glyph::from_digit(center.into()),
+            _ => return Err(DecodeError::InvalidCenter(center)),
+        };
+
+        let len = ring_bytes(cells);
+        if rest.len() < len {
+            return Err(DecodeError::TooShort);
+        } else if rest.len() > len {
+            return Err(DecodeError::TooLong);
+        }
+        let mut int = [0; 16];
+        int[..len].copy_from_slice(rest);
+        let int = u128::from_le_bytes(int);
+        if int >= 3u128.pow(cells.into()) {
+            return Err(DecodeError::RingOutOfRange);
+        }
+
+        Ok(Self {
+            center,
+            ring: Ring { int, cells },
+        })
+    }
+
+    /// Get a short code for this board which can be pasted into chat or a URL, and turned back into the board with
+    /// `from_code`. An 8-cell board gets a 7-character code.
+    ///
+    /// This is just `to_bytes` in URL-safe base64, without any padding. Whose turn it is doesn't need to be included,
+    /// since it can always be worked out from the glyphs.
+    pub fn to_code(&self) -> String {
+        encode_code(&self.to_bytes())
+    }
+
+    /// The inverse of `to_code`. Whitespace around the code is ignored, since it tends to get picked up when copying.
+    ///
+    /// Each board only has one code, so this rejects codes with anything set in the bits left over after the last byte,
+    /// as well as the ones `from_bytes` would reject.
+    pub fn from_code(code: &str) -> Result<Self, DecodeError> {
+        let mut bytes = Vec::new();
+        // The bits which have been decoded but haven't made a whole byte yet, and how many of them there are.
+        let mut bits = 0u32;
+        let mut len = 0;
+        for c in code.trim().chars() {
+            let digit = CODE_ALPHABET
+                .iter()
+                .position(|&digit| char::from(digit) == c)
+                .ok_or(DecodeError::InvalidChar(c))?;
+            bits = bits << 6 | digit as u32;
+            len += 6;
+            if len >= 8 {
+                len -= 8;
+                bytes.push((bits >> len) as u8);
+                bits &= (1 << len) - 1;
+            }
+        }
+        // `to_code` never leaves a whole character's worth of bits over, and always leaves the padding as zeroes.
+        if len >= 6 || bits != 0 {
+            return Err(DecodeError::TooLong);
+        }
+        Self::from_bytes(&bytes)
+    }
+
+    /// Work out which move was made to get from this board to `later`.
+    ///
+    /// The boards have to be exactly one legal move apart; they aren't allowed to be rotations or reflections of it.
+    pub fn diff(&self, later: &Board) -> Result<Move, DiffError> {
+        if self.ring.len() != later.ring.len() {
+            return Err(DiffError::DifferentSizes {
+                before: self.ring.len(),
+                after: later.ring.len(),
+            });
+        }
+
+        let mut changes = self
+            .cells()
+            .zip(later.cells())
+            .filter(|((_, before), (_, after))| before != after);
+        let ((pos, before), (_, after)) = changes.next().ok_or(DiffError::NoChange)?;
+        let others = changes.count();
+        if others > 0 {
+            // There are at most 81 cells, so this always fits.
+            return Err(DiffError::TooManyChanges(others as u8 + 1));
+        }
+        let player = match (before, after.player()) {
+            (Glyph::None, Some(player)) => player,
+            _ => return Err(DiffError::NotAPlacement(pos)),
+        };
+
+        let mv = Move::place(pos, player);
+        // Check that it was actually allowed.
+        let mut board = *self;
+        board.apply(mv).map_err(DiffError::Illegal)?;
+        Ok(mv)
+    }
+
+    /// Like `play`, but returns a new board with the move made instead of changing this one.
+    pub fn with_move(&self, mv: Position) -> Result<Board, MoveError> {
+        let mut board = *self;
+        board.play(mv)?;
+        Ok(board)
+    }
+
+    /// Whether playing `mv` would win the game straight away for whoever's turn it is.
+    ///
+    /// Returns `false` if `mv` isn't allowed.
+    pub fn is_winning_move(&self, mv: Position) -> bool {
+        let turn = self.turn();
+        self.with_move(mv)
+            .is_ok_and(|board| board.winner() == Some(turn))
+    }
+
+    /// Guess how good the board is for whoever's turn it is, without looking ahead at all.
+    ///
+    /// Every line which only one player has glyphs in counts towards them, and more so the more glyphs they have in it.
+    /// A won game is worth ±`i8::MAX`, which nothing else can reach.
+    pub fn evaluate(&self) -> i8 {
+        let turn = self.turn();
+        match self.result() {
+            GameResult::Won(glyph) if glyph == turn => return i8::MAX,
+            GameResult::Won(_) => return -i8::MAX,
+            GameResult::Draw => return 0,
+            GameResult::InProgress => {}
+        }
+
+        let score_line = |line: [Glyph; 3]| -> i8 {
+            let mine = line.iter().filter(|&&glyph| glyph == turn).count() as i8;
+            let theirs = line
+                .iter()
+                .filter(|&&glyph| glyph != turn && glyph.is_occupied())
+                .count() as i8;
+            match (mine, theirs) {
+                (mine, 0) => mine * mine,
+                (0, theirs) => -theirs * theirs,
+                // Both players have a glyph in this line, so nobody can win with it.
+                _ => 0,
+            }
+        };
+
+        self.lines()
+            .into_iter()
+            .map(|line| score_line(self.line_glyphs(line)))
+            .sum()
+    }
+
+    /// Check whether nobody can possibly win anymore, even though there might still be empty cells left.
+    ///
+    /// A player can only still win a line if their opponent has no glyphs in it,
+    /// and they have enough moves left before the board fills up to fill in the rest of it.
+    ///
+    /// Returns `false` if somebody has already won.
+    pub fn is_unwinnable(&self) -> bool {
+        if self.winner().is_some() {
+            return false;
+        }
+
+        let empty = self.empty_cells();
+        let turn = self.turn();
+        // Players take turns, so whoever's turn it is gets the extra move if there's an odd number of cells left.
+        let moves_left = |player: Player| {
+            if player == turn {
+                empty.div_ceil(2)
+            } else {
+                empty / 2
+            }
+        };
+
+        !self.lines().into_iter().any(|line| {
+            let glyphs = self.line_glyphs(line);
+            [Player::X, Player::O].iter().any(|&player| {
+                !glyphs.contains(&player.opponent().glyph())
+                    && glyphs.iter().filter(|glyph| glyph.is_empty()).count() <= moves_left(player)
+            })
+        })
+    }
+
+    /// Get every line which would win the game if one player filled it, whether or not anybody has.
+    ///
+    /// On rings with an odd number of cells, the lines through the center go to the cells next to where the opposite
+    /// cell would be; see `Line::Center`.
+    pub fn lines(&self) -> Vec<Line> {
+        Line::all(self.ring.len()).collect()
+    }
+
+    /// Get the cells of every line which would win the game on a board with `cells` cells in its ring, in the same
+    /// order as `lines`.
+    ///
+    /// This only depends on the size of the board, so it can be worked out once up front, e.g. for building bitmasks.
+    pub fn all_win_lines(cells: u8) -> Vec<[Position; 3]> {
+        Line::all(cells).map(|line| line.cells(cells)).collect()
+    }
+
+    fn line_glyphs(&self, line: Line) -> [Glyph; 3] {
+        line.cells(self.ring.len())
+            .map(|mv| self.get(mv).expect("line is out of range"))
+    }
+
+    /// Get the moves which would finish off a line for `player`, because they've already got the other two cells of it.
+    ///
+    /// Each move only shows up once, even if it would finish more than one line.
+    /// This doesn't care whose turn it is, so it works just as well for spotting what the opponent is threatening.
+    pub fn near_wins(&self, player: Player) -> Vec<Position> {
+        let mut out = Vec::new();
+
+        for line in self.lines() {
+            let cells = line.cells(self.ring.len());
+            let glyphs = self.line_glyphs(line);
+            if glyphs.iter().filter(|&&glyph| glyph == player).count() != 2 {
+                continue;
+            }
+
+            if let Some(i) = glyphs.iter().position(|glyph| glyph.is_empty()) {
+                if !out.contains(&cells[i]) {
+                    out.push(cells[i]);
+                }
+            }
+        }
+
+        out
+    }
+
+    /// Get the positions where `player` could win on their next move.
+    ///
+    /// This is the same as `near_wins`, except that there aren't any threats once the game's over.
+    pub fn threats(&self, player: Player) -> Vec<Position> {
+        if self.result() != GameResult::InProgress {
+            return Vec::new();
+        }
+
+        self.near_wins(player)
+    }
+
+    /// Find the best move for whoever's turn it is, assuming that both players play perfectly from here on.
+    ///
+    /// This searches the whole game tree, so it gets very slow on big, empty boards.
+    /// If you're going to be asking for lots of moves, use a `Solver` instead so that the work gets reused.
+    ///
+    /// Returns `None` if the game is already over.
+    #[cfg(feature = "std")]
+    pub fn best_move(&self) -> Option<Position> {
+        // There are never more than `Ring::MAX_CELLS + 1` cells, so the search can't get any deeper than this.
+        self.best_move_depth(u8::MAX)
+    }
+
+    /// Like `best_move`, except that it only looks `max_depth` moves ahead, and then guesses with `evaluate`.
+    ///
+    /// This is much quicker on big boards, but it plays worse the shallower it searches:
+    /// it won't see any wins or losses further away than `max_depth`.
+    #[cfg(feature = "std")]
+    pub fn best_move_depth(&self, max_depth: u8) -> Option<Position> {
+        Solver::new().best_move_depth(self, max_depth)
+    }
+
+    fn empty_cells(&self) -> usize {
+        self.ring
+            .iter()
+            .chain([self.center])
+            .filter(|glyph| glyph.is_empty())
+            .count()
+    }
+
+    fn get(&self, mv: Position) -> Result<Glyph, MoveError> {
+        match mv {
+            Position::Center => Ok(self.center),
+            Position::Ring(index) => self.ring.get_checked(index).ok_or(MoveError::OutOfRange {
+                index,
+                cells: self.ring.len(),
+            }),
+        }
+    }
+
+    fn set(&mut self, mv: Position, glyph: Glyph) {
+        match mv {
+            Position::Center => self.center = glyph,
+            Position::Ring(i) => self.ring.set(i, glyph),
+        }
+    }
+}
+
+/// Like `Ring`, this is symmetry-aware: boards are equal if they have the same center,
+/// and their rings are rotations or reflections of each other. Use `Board::is_identical` for exact comparisons.
+impl PartialEq for Board {
+    fn eq(&self, other: &Self) -> bool {
+        self.center == other.center && self.ring == other.ring
+    }
+}
+
+impl Eq for Board {}
+
+/// Boards are ordered by their rings first (see `Ring`'s `Ord`), and then by their centers.
+impl PartialOrd for Board {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Board {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.ring
+            .cmp(&other.ring)
+            .then_with(|| self.center.cmp(&other.center))
+    }
+}
+
+impl Hash for Board {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.center.hash(state);
+        // This hashes the canonical form, so it's consistent with `PartialEq`.
+        self.ring.hash(state);
+    }
+}
+
+/// Shows the center, then a `|`, then the ring like its `Display` does, e.g. `"X| XO XO X"`.
+/// The alternate form (`{:#}`) is `Board::render_ascii` instead, when the `std` feature is on.
+impl Display for Board {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        #[cfg(feature = "std")]
+        if f.alternate() {
+            return write!(f, "{}", self.render_ascii());
+        }
+
+        write!(f, "{}|{}", self.center.to_char(), self.ring)
+    }
+}
+
+impl Debug for Board {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        <Self as Display>::fmt(self, f)
+    }
+}
+
+/// The inverse of `Display`.
+impl FromStr for Board {
+    type Err = ParseRingError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut chars = s.chars();
+        let center = match (chars.next(), chars.next()) {
+            (Some(c), Some('|')) => Glyph::from_char(c).ok_or(ParseRingError::InvalidChar(c))?,
+            _ => return Err(ParseRingError::NoCenter),
+        };
+
+        Ok(Self {
+            center,
+            ring: chars.as_str().parse()?,
+        })
+    }
+}
+
+/// A blank board with `Board::DEFAULT_CELLS` cells.
+impl Default for Board {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_CELLS)
+    }
+}
+
+/// Any board at all, including ones that couldn't come up in a real game.
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for Board {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        Ok(Self {
+            center: u.arbitrary()?,
+            ring: u.arbitrary()?,
+        })
+    }
+}
+
+/// A more readable way of setting up a board than lots of calls to `Ring::set`, e.g.
+/// `BoardBuilder::new(8).center(Glyph::X).set(3, Glyph::O).build()`.
+///
+/// Nothing gets checked until `build`, so that the calls can be chained without unwrapping after each one.
+#[derive(Debug, Clone)]
+pub struct BoardBuilder {
+    cells: u8,
+    center: Glyph,
+    /// The ring given to `ring_from_str`, if there was one.
+    base: Option<Result<Ring, ParseRingError>>,
+    ring: Vec<(u8, Glyph)>,
+}
+
+impl BoardBuilder {
+    pub fn new(cells: u8) -> Self {
+        Self {
+            cells,
+            center: Glyph::None,
+            base: None,
+            ring: Vec::new(),
+        }
+    }
+
+    /// Change the number of cells in the ring.
+    pub fn cells(mut self, cells: u8) -> Self {
+        self.cells = cells;
+        self
+    }
+
+    pub fn center(mut self, glyph: Glyph) -> Self {
+        self.center = glyph;
+        self
+    }
+
+    /// Start the ring off as `s`, in the same form as `Ring`'s `Display` (e.g. `"X O  OX "`).
+    ///
+    /// It has to have the right number of cells, or `build` will fail. Any calls to `set` still apply on top of it.
+    pub fn ring_from_str(mut self, s: &str) -> Self {
+        self.base = Some(s.parse());
+        self
+    }
+
+    /// Put `glyph` at index `i` of the ring. Unlike `Ring::set`, this doesn't wrap around; out-of-range indices make `build` fail instead.
+    pub fn set(mut self, i: u8, glyph: Glyph) -> Self {
+        self.ring.push((i, glyph));
+        self
+    }
+
+    pub fn build(self) -> Result<Board, BoardError> {
+        let mut board = Board::try_new(self.cells)?;
+        board.center = self.center;
+        if let Some(base) = self.base {
+            let ring = base.map_err(BoardError::Parse)?;
+            if ring.len() != self.cells {
+                return Err(BoardError::WrongLength {
+                    expected: self.cells,
+                    found: ring.len(),
+                });
+            }
+            board.ring = ring;
+        }
+        board.ring.try_set_all(self.ring)?;
+
+        Ok(board)
+    }
+}
+
+impl Default for BoardBuilder {
+    fn default() -> Self {
+        Self::new(Board::DEFAULT_CELLS)
+    }
+}
+
+/// This is represented internally as a ternary integer, where 0 is an empty cell, 1 is an X, and 2 is an O.
+///
+/// Note that `PartialEq` and `Hash` are symmetry-aware: rings which are rotations or reflections of each other are equal,
+/// and so they'll collide as keys in a `HashMap`. Wrap rings in `Literal` to compare them exactly as they are instead.
+#[derive(Clone, Copy)]
+pub struct Ring {
+    // 128 bits is big enough to store rings of up to 80 cells, which is way more than anybody's going to want to play on.
+    // It'd be possible to store it a bit more efficiently by enumerating all the boards, but eh.
+    int: u128,
+
+    // This should be at most `MAX_CELLS` to work properly.
+    cells: u8,
+}
+
+impl Ring {
+    /// The most cells a ring can have. 3^80 is just under 2^127, so any more wouldn't fit.
+    pub const MAX_CELLS: u8 = 80;
+
+    /// Create a new, empty ring.
+    ///
+    /// Panics if `cells` is 0 or more than `MAX_CELLS`.
+    pub const fn new(cells: u8) -> Self {
+        // `try_new` can't be used here, since formatting the error isn't allowed in a `const fn`.
+        assert!(cells > 0, "a ring needs at least one cell");
+        assert!(cells <= Self::MAX_CELLS, "too many cells for a ring");
+        Self { int: 0, cells }
+    }
+
+    /// Create a new, empty ring, or an error if `cells` is 0 or more than `MAX_CELLS`.
+    pub fn try_new(cells: u8) -> Result<Self, BoardError> {
+        BoardError::check_cells(cells)?;
+        Ok(Self { int: 0, cells })
+    }
+
+    pub fn canonicalize(self) -> Self {
+        self.canonicalize_with_transform().0
+    }
+
+    /// Like `canonicalize`, but also returns the symmetry which turns this ring into its canonical form,
+    /// so that cells of the canonical ring can be mapped back to where they are in this one with `Dihedral::invert`.
+    pub fn canonicalize_with_transform(self) -> (Self, Dihedral) {
+        // The ring's integer has the first cell as its most significant digit, so the biggest rotation is the one whose
+        // digits come first alphabetically in reverse. Flipping the digits around turns that into the smallest one,
+        // which Booth's algorithm can find in linear time, rather than trying every rotation.
+        let flipped: Vec<u8> = self.iter().map(|glyph| 2 - glyph as u8).collect();
+        let n = flipped.len();
+
+        // Starting from index `start` means moving it to index 0, i.e. rotating by `n - start`.
+        let start = least_rotation(&flipped);
+        let rotate = Dihedral::Rotate(((n - start) % n) as u8);
+
+        // The same thing works for the reflections, by doing it on the cells in reverse order.
+        // Reading backwards from index `k` is what `Reflect(k)` does, and `start` in the reversed list is `n - 1 - start`.
+        let reversed: Vec<u8> = flipped.iter().rev().copied().collect();
+        let start = least_rotation(&reversed);
+        let reflect = Dihedral::Reflect((n - 1 - start) as u8);
+
+        let rotated = self.transform(rotate);
+        let reflected = self.transform(reflect);
+        if reflected.int > rotated.int {
+            (reflected, reflect)
+        } else {
+            (rotated, rotate)
+        }
+    }
+
+    /// Another name for `canonicalize_with_transform`.
+    pub fn rotate_to_canonical(self) -> (Self, Dihedral) {
+        self.canonicalize_with_transform()
+    }
+
+    /// Empty every cell in the ring.
+    pub fn clear(&mut self) {
+        self.int = 0;
+    }
+
+    /// Get the position of this ring's canonical form in a list of all the canonical rings with the same number of cells,
+    /// sorted by their internal integer. Rings which are rotations or reflections of each other get the same index.
+    ///
+    /// This is handy for building lookup tables of every possible position.
+    /// The list gets built (and cached) the first time it's needed for a given size by going through every possible ring,
+    /// so this is only really practical for rings of up to about 12 cells.
+    ///
+    /// Panics if the ring has more than 20 cells.
+    #[cfg(feature = "std")]
+    pub fn canonical_index(&self) -> u32 {
+        let table = canonical_table(self.cells);
+        let index = table
+            .binary_search(&self.canonicalize().int)
+            .expect("canonical ring missing from table");
+        // There are at most 3^20 rings, which fits in a `u32`.
+        index as u32
+    }
+
+    /// The inverse of `canonical_index`: get the canonical ring with `cells` cells at `index`,
+    /// or `None` if there aren't that many canonical rings.
+    ///
+    /// Panics if `cells` is more than 20.
+    #[cfg(feature = "std")]
+    pub fn from_canonical_index(cells: u8, index: u32) -> Option<Self> {
+        let int = *canonical_table(cells).get(usize::try_from(index).ok()?)?;
+        Some(Self { int, cells })
+    }
+
+    /// Get how many canonical rings there are with `cells` cells, i.e. one more than the biggest `canonical_index`.
+    ///
+    /// Panics if `cells` is more than 20.
+    #[cfg(feature = "std")]
+    pub fn canonical_count(cells: u8) -> u32 {
+        // There are at most 3^20 rings, which fits in a `u32`.
+        canonical_table(cells).len() as u32
+    }
+
+    /// Iterate over the cells of the ring, starting from index 0.
+    pub fn iter(&self) -> Cells {
+        self.into_iter()
+    }
+
+    pub const fn len(&self) -> u8 {
+        self.cells
+    }
+
+    /// Count how many cells in the ring contain `glyph`.
+    pub fn count(&self, glyph: Glyph) -> u8 {
+        let count = self.iter().filter(|&cell| cell == glyph).count();
+        // There can't be more than `MAX_CELLS` cells, so this always fits.
+        count as u8
+    }
+
+    /// Whether there aren't any glyphs in the ring.
+    ///
+    /// Note that this is about the cells' contents, not the number of cells: a ring always has at least one cell.
+    pub const fn is_empty(&self) -> bool {
+        // An empty cell is a 0 digit, so this is much quicker than going through the cells.
+        self.int == 0
+    }
+
+    /// Count how many cells in the ring have a glyph in them.
+    pub fn occupied(&self) -> u8 {
+        self.cells - self.count(Glyph::None)
+    }
+
+    /// Whether every cell in the ring has a glyph in it.
+    pub fn is_full(&self) -> bool {
+        self.occupied() == self.cells
+    }
+
+    /// Get the glyph at index `i`, wrapping around if `i` is past the end of the ring.
+    ///
+    /// This is the same as `get_wrapping`; use `get_checked` if an out-of-range index would be a bug.
+    pub const fn get(&self, i: u8) -> Glyph {
+        self.get_wrapping(i)
+    }
+
+    /// Put `cell` at index `i`, wrapping around if `i` is past the end of the ring.
+    ///
+    /// This is the same as `set_wrapping`; use `set_checked` if an out-of-range index would be a bug.
+    pub const fn set(&mut self, i: u8, cell: Glyph) {
+        self.set_wrapping(i, cell)
+    }
+
+    /// Get the glyph at index `i`, or `None` if `i` is past the end of the ring.
+    pub fn get_checked(&self, i: u8) -> Option<Glyph> {
+        (i < self.cells).then(|| self.get_wrapping(i))
+    }
 
-impl Glyph {
-    fn from_digit(digit: u32) -> Self {
-        match digit {
-            0 => Self::None,
-            1 => Self::X,
-            2 => Self::O,
-            _ => unreachable!(),
+    /// Put `cell` at index `i`, or return an error if `i` is past the end of the ring.
+    pub fn set_checked(&mut self, i: u8, cell: Glyph) -> Result<(), MoveError> {
+        if i >= self.cells {
+            return Err(MoveError::OutOfRange {
+                index: i,
+                cells: self.cells,
+            });
         }
+
+        self.set_wrapping(i, cell);
+        Ok(())
     }
-}
 
-pub enum Win {
-    /// A win which is entirely located along the ring.
-    Ring {
-        /// The index in the ring at which this win starts; the two cells after it are also part of the win.
-        index: u8,
-    },
-    /// A win which goes through the center.
-    Center {
-        /// The index of one of the cells on the ring which forms this win; the other one is on the opposite side of the ring.
-        index: u8,
-    },
-}
+    /// Get the glyph at index `i % len`, so that indices past the end wrap back around to the start of the ring.
+    pub const fn get_wrapping(&self, i: u8) -> Glyph {
+        let i = i % self.cells;
 
-pub struct Board {
-    pub center: Glyph,
-    pub ring: Ring,
-}
+        Glyph::from_digit(self.int / 3u128.pow((self.cells - i - 1) as u32) % 3)
+    }
 
-impl Board {
-    /// Create a new, blank board with `cells` around the outside.
-    pub fn new(cells: u8) -> Self {
+    /// Put `cell` at index `i % len`, so that indices past the end wrap back around to the start of the ring.
+    pub const fn set_wrapping(&mut self, i: u8, cell: Glyph) {
+        let i = i % self.cells;
+
+        let multiplier = 3u128.pow((self.cells - i - 1) as u32);
+
+        // Apply the difference between the value of the existing digit there and the new digit.
+        let digit = self.int / multiplier % 3;
+        let new_digit = match cell {
+            Glyph::None => 0,
+            Glyph::X => 1,
+            Glyph::O => 2,
+        };
+        let diff = new_digit - digit as i32;
+        // Signed and unsigned addition (and multiplication) are actually the same operation, so just pretend this is a `u128` to make the compiler let us do this.
+        self.int = self
+            .int
+            .wrapping_add((diff as u128).wrapping_mul(multiplier));
+    }
+
+    /// Rotate the ring `n` places backwards, so the cell at index `i` ends up at `i - n`, and the first `n` cells wrap
+    /// around to the end. `n` can be bigger than the ring, in which case it wraps around too.
+    ///
+    /// This is what `<<` does.
+    pub const fn rotate_left(self, n: u8) -> Self {
+        let n = n % self.cells;
+
+        // Get rid of the digits which are going to go off the end first, so that the multiplication can't overflow.
+        let truncated = self.int % 3u128.pow((self.cells - n) as u32) * 3u128.pow(n as u32);
+        let wrapped = self.int / 3u128.pow((self.cells - n) as u32);
         Self {
-            center: Glyph::None,
-            ring: Ring::new(cells),
+            int: truncated + wrapped,
+            cells: self.cells,
         }
     }
 
-    pub fn winner(&self) -> Glyph {
-        // The `.cycle().take(10)` means that we put the first two on the end as well,
-        // so that we pick up matches on the wrapping-around point.
-        for cells in self
-            .ring
-            .into_iter()
-            .cycle()
-            .take((self.ring.len() + 2).into())
-            .collect::<Vec<_>>()
-            .windows(3)
-        {
-            if cells == [Glyph::X; 3] {
-                return Glyph::X;
-            } else if cells == [Glyph::O; 3] {
-                return Glyph::O;
-            }
+    /// Rotate the ring `n` places forwards, so the cell at index `i` ends up at `i + n`, and the last `n` cells wrap
+    /// around to the start. `n` can be bigger than the ring, in which case it wraps around too.
+    ///
+    /// This is what `>>` does.
+    pub const fn rotate_right(self, n: u8) -> Self {
+        let n = n % self.cells;
+        // The digits which are getting wrapped.
+        let mut wrapped = self.int % 3u128.pow(n as u32);
+        // Move them up to the most significant digits where they'll end up.
+        wrapped *= 3u128.pow((self.cells - n) as u32);
+
+        let truncated = self.int / 3u128.pow(n as u32);
+
+        Self {
+            int: truncated + wrapped,
+            cells: self.cells,
         }
+    }
 
-        if self.center == Glyph::None {
-            // If the middle is blank, there can't be a win through the middle.
-            return Glyph::None;
+    /// Call `set` for every index and glyph in `cells`.
+    pub fn set_all(&mut self, cells: impl IntoIterator<Item = (u8, Glyph)>) {
+        for (i, glyph) in cells {
+            self.set(i, glyph);
         }
+    }
 
-        debug_assert!(self.ring.cells % 2 == 0);
+    /// Like `set_all`, except that indices past the end of the ring are an error rather than wrapping around.
+    ///
+    /// All the indices are checked before anything gets set, so the ring is left untouched if this fails.
+    pub fn try_set_all(
+        &mut self,
+        cells: impl IntoIterator<Item = (u8, Glyph)>,
+    ) -> Result<(), BoardError> {
+        let cells: Vec<_> = cells.into_iter().collect();
 
-        // Iterate over the pairs of cells on opposite sides of the board,
-        // by offsetting the second iterator by half.
-        for (a, b) in self
-            .ring
-            .into_iter()
-            .zip(self.ring.into_iter().skip((self.ring.cells / 2).into()))
-        {
-            if a == self.center && b == self.center {
-                return self.center;
+        for &(index, _) in &cells {
+            if index >= self.cells {
+                return Err(BoardError::OutOfRange {
+                    index,
+                    cells: self.cells,
+                });
             }
         }
 
-        Glyph::None
+        self.set_all(cells);
+
+        Ok(())
     }
 
-    /// Get all of the ways in which the game has been won.
-    pub fn wins(&self) -> Vec<Win> {
-        let mut out = Vec::new();
+    /// Get the ring as a string of base-3 digits, where 0 is an empty cell, 1 is an X, and 2 is an O.
+    ///
+    /// This always has one digit per cell, including leading zeroes.
+    pub fn to_base3(&self) -> String {
+        self.fmt_with('0', '1', '2')
+    }
 
-        // The `.cycle().take(10)` means that we put the first two on the end as well,
-        // so that we pick up matches on the wrapping-around point.
-        for (i, cells) in self
-            .ring
-            .into_iter()
-            .cycle()
-            .take((self.ring.len() + 2).into())
-            .collect::<Vec<_>>()
-            .windows(3)
-            .enumerate()
-        {
-            if cells == [Glyph::X; 3] || cells == [Glyph::O; 3] {
-                out.push(Win::Ring {
-                    index: i.try_into().expect("too many cells"),
-                })
-            }
+    /// The inverse of `to_base3`.
+    pub fn from_base3(digits: &str) -> Result<Self, ParseRingError> {
+        Self::parse_with(digits, '0', '1', '2')
+    }
+
+    /// Parse a ring in its `Display` form, in a way that works in a `const`. This is what `ring!` uses.
+    ///
+    /// Panics if `s` isn't valid, which is a compile error when it's evaluated as a `const`.
+    #[doc(hidden)]
+    pub const fn __parse_const(s: &str) -> Self {
+        let bytes = s.as_bytes();
+        assert!(!bytes.is_empty(), "a ring needs at least one cell");
+        assert!(
+            bytes.len() <= Ring::MAX_CELLS as usize,
+            "too many cells for a ring"
+        );
+
+        let mut int = 0;
+        let mut i = 0;
+        while i < bytes.len() {
+            int = int * 3
+                + match bytes[i] {
+                    b' ' => 0,
+                    b'X' => 1,
+                    b'O' => 2,
+                    _ => panic!("invalid cell in ring"),
+                };
+            i += 1;
         }
 
-        debug_assert!(self.ring.cells % 2 == 0);
-
-        if self.center != Glyph::None {
-            // Iterate over the pairs of cells on opposite sides of the board,
-            // by offsetting the second iterator by half.
-            for (i, (a, b)) in self
-                .ring
-                .into_iter()
-                .zip(self.ring.into_iter().skip((self.ring.cells / 2).into()))
-                .enumerate()
-            {
-                if a == self.center && b == self.center {
-                    out.push(Win::Center {
-                        index: i.try_into().expect("too many cells"),
-                    })
-                }
-            }
+        Self {
+            int,
+            cells: bytes.len() as u8,
         }
+    }
+
+    /// The inverse of `fmt_with`.
+    fn parse_with(s: &str, empty: char, x: char, o: char) -> Result<Self, ParseRingError> {
+        let len = s.chars().count();
+        if len == 0 {
+            return Err(ParseRingError::Empty);
+        } else if len > Ring::MAX_CELLS.into() {
+            return Err(ParseRingError::TooLong(len));
+        }
+
+        s.chars()
+            .map(|c| {
+                if c == empty {
+                    Ok(Glyph::None)
+                } else if c == x {
+                    Ok(Glyph::X)
+                } else if c == o {
+                    Ok(Glyph::O)
+                } else {
+                    Err(ParseRingError::InvalidChar(c))
+                }
+            })
+            .collect()
+    }
 
+    /// Render the ring like `Display` does, but with different characters for each kind of cell,
+    /// e.g. `ring.fmt_with('.', 'X', 'O')` to make the empty cells visible.
+    pub fn fmt_with(&self, empty: char, x: char, o: char) -> String {
+        let mut out = String::new();
+        self.write_with(&mut out, empty, x, o).unwrap();
         out
     }
-}
 
-/// This is represented internally as a ternary integer, where 0 is an empty cell, 1 is an X, and 2 is an O.
-#[derive(Clone, Copy)]
-pub struct Ring {
-    // 32 bits is big enough to store rings of up to 20 cells. I'd say that's a reasonable limit.
-    // It'd be possible to store it a bit more efficiently by enumerating all the boards, but eh.
-    int: u32,
+    fn write_with(&self, w: &mut impl Write, empty: char, x: char, o: char) -> fmt::Result {
+        for cell in self {
+            w.write_char(match cell {
+                Glyph::None => empty,
+                Glyph::X => x,
+                Glyph::O => o,
+            })?;
+        }
+        Ok(())
+    }
 
-    // This should be at most 20 to work properly.
-    cells: u8,
-}
+    /// Apply the symmetry `d` to the ring.
+    pub fn transform(self, d: Dihedral) -> Self {
+        match d {
+            Dihedral::Rotate(k) => self.rotate_right(k),
+            Dihedral::Reflect(k) => self.reflect(k),
+        }
+    }
 
-impl Ring {
-    pub fn new(cells: u8) -> Self {
-        Self { int: 0, cells }
+    /// Mirror the ring about one of its axes of symmetry, so the cell at index `i` ends up at `axis - i`.
+    ///
+    /// Axes are numbered in half-cells around the ring: axis `2 * j` goes through the middle of cell `j`, and axis
+    /// `2 * j + 1` goes between cells `j` and `j + 1`. Each axis crosses the ring twice, so `axis` and `axis + len` are
+    /// the same one, and any `axis` works. `reversed` is the same as `reflect(len - 1)`.
+    pub fn reflect(self, axis: u8) -> Self {
+        // Reversing sends `i` to `cells - 1 - i`, so it needs another rotation to end up at `axis - i`.
+        self.reversed()
+            .rotate_right((axis % self.cells + 1) % self.cells)
     }
 
-    pub fn canonicalize(self) -> Self {
-        let max = (0..self.cells)
-            .map(|n| self << n)
-            .flat_map(|ring| [ring, ring.reverse()])
-            .max_by_key(|ring| ring.int);
-        max.unwrap()
+    /// Get every rotation of the ring, starting with the ring itself, in the same order as `Dihedral::Rotate(k)`.
+    pub fn rotations(self) -> impl Iterator<Item = Self> {
+        (0..self.cells).map(move |k| self.rotate_right(k))
     }
 
-    pub fn len(&self) -> u8 {
-        self.cells
+    /// Get the result of applying every symmetry to the ring, in the same order as `Dihedral::all`.
+    ///
+    /// Rings with symmetries of their own show up more than once.
+    pub fn symmetries(self) -> impl Iterator<Item = Self> {
+        Dihedral::all(self.cells).map(move |d| self.transform(d))
     }
 
-    pub fn get(&self, i: u8) -> Glyph {
-        let i = i % self.cells;
+    /// Find a symmetry which turns this ring into `other`, or `None` if they aren't symmetric (i.e. they aren't equal).
+    pub fn symmetry_to(&self, other: &Ring) -> Option<Dihedral> {
+        if self.cells != other.cells {
+            return None;
+        }
 
-        Glyph::from_digit(self.int / 3u32.pow((self.cells - i - 1).into()) % 3)
+        Dihedral::all(self.cells).find(|&d| self.transform(d).int == other.int)
     }
 
-    pub fn set(&mut self, i: u8, cell: Glyph) {
-        let i = i % self.cells;
+    /// Apply `f` to every cell in the ring, e.g. `ring.map(|glyph| glyph.player().map(Player::opponent).into())` to
+    /// swap all the Xs and Os.
+    pub fn map(self, f: impl Fn(Glyph) -> Glyph) -> Self {
+        self.iter().map(f).collect()
+    }
 
-        let multiplier = 3u32.pow((self.cells - i - 1).into());
+    /// Get the cells of the ring as an array, or `None` if the ring doesn't have exactly `N` cells.
+    pub fn to_array<const N: usize>(&self) -> Option<[Glyph; N]> {
+        if usize::from(self.cells) != N {
+            return None;
+        }
 
-        // Apply the difference between the value of the existing digit there and the new digit.
-        let digit = self.int / multiplier % 3;
-        let new_digit = match cell {
-            Glyph::None => 0,
-            Glyph::X => 1,
-            Glyph::O => 2,
-        };
-        let diff = new_digit - digit as i32;
-        // Signed and unsigned addition are actually the same operation, so just pretend this is a `u32` to make the compiler let us do this.
-        self.int = self.int.wrapping_add((diff * multiplier as i32) as u32);
+        let mut out = [Glyph::None; N];
+        for (slot, glyph) in out.iter_mut().zip(self) {
+            *slot = glyph;
+        }
+        Some(out)
     }
 
-    fn reverse(self) -> Self {
+    /// Get the mirror image of the ring, i.e. the same cells in the opposite order.
+    pub fn reversed(self) -> Self {
         // I can't think of any fancier way of doing this.
-        self.into_iter().rev().collect()
+        self.iter().rev().collect()
+    }
+}
+
+/// Find the index to start reading `digits` from, wrapping around the end, to get the alphabetically smallest result.
+///
+/// This is Booth's algorithm, which is basically Knuth-Morris-Pratt's failure function run over `digits` twice.
+fn least_rotation(digits: &[u8]) -> usize {
+    let n = digits.len();
+    let digit = |i: usize| digits[i % n];
+
+    // `failure[i]` is the length of the longest proper suffix of the first `i + 1` digits from `k` which is also a
+    // prefix of them, minus one (so -1 means there isn't one).
+    let mut failure = vec![-1isize; 2 * n];
+    let mut k = 0;
+    for j in 1..2 * n {
+        let d = digit(j);
+        let mut i = failure[j - k - 1];
+        while i != -1 && d != digit(k + (i + 1) as usize) {
+            if d < digit(k + (i + 1) as usize) {
+                k = j - (i + 1) as usize;
+            }
+            i = failure[i as usize];
+        }
+
+        if d != digit(k + (i + 1) as usize) {
+            // `i` must be -1 here, since that's the only way the loop can stop without a match.
+            if d < digit(k) {
+                k = j;
+            }
+            failure[j - k] = -1;
+        } else {
+            failure[j - k] = i + 1;
+        }
     }
+
+    k % n
+}
+
+/// Get the sorted list of the internal integers of every canonical ring with `cells` cells.
+#[cfg(feature = "std")]
+fn canonical_table(cells: u8) -> &'static [u128] {
+    // Nobody's going to be waiting around for all 3^21 rings to get checked anyway,
+    // and it means the indices always fit in a `u32`.
+    static TABLES: [OnceLock<Vec<u128>>; 21] = [const { OnceLock::new() }; 21];
+    assert!(cells <= 20, "canonical indices only go up to 20 cells");
+
+    TABLES[usize::from(cells)].get_or_init(|| {
+        (0..3u128.pow(cells.into()))
+            .filter(|&int| Ring { int, cells }.canonicalize().int == int)
+            .collect()
+    })
 }
 
-// This doesn't really behave the same as a bit-shift, since it wraps around to the other side, but it's fine.
+/// The same as `Ring::rotate_left`.
+///
+/// Note that unlike shifting an integer, nothing gets lost off the end: cells wrap around to the other side of the ring.
 impl Shl<u8> for Ring {
     type Output = Self;
 
     fn shl(self, rhs: u8) -> Self::Output {
-        let rhs = rhs % self.cells;
+        self.rotate_left(rhs)
+    }
+}
 
-        // We want any bits which go off the end of the number to be truncated, which is all that wrapping really is.
-        let truncated = self.int.wrapping_mul(3u32.pow(rhs.into())) % 3u32.pow(self.cells.into());
-        let wrapped = self.int / 3u32.pow((self.cells - rhs).into());
-        Self {
-            int: truncated + wrapped,
-            cells: self.cells,
-        }
+impl ShlAssign<u8> for Ring {
+    fn shl_assign(&mut self, rhs: u8) {
+        *self = self.rotate_left(rhs);
     }
 }
 
+/// The same as `Ring::rotate_right`.
+///
+/// Like `<<`, this wraps around rather than losing cells off the end.
 impl Shr<u8> for Ring {
     type Output = Self;
 
     fn shr(self, rhs: u8) -> Self::Output {
-        let rhs = rhs % self.cells;
-        // The digits which are getting wrapped.
-        let mut wrapped = self.int % 3u32.pow(rhs.into());
-        // Move them up to the most significant digits where they'll end up.
-        wrapped *= 3u32.pow((self.cells - rhs).into());
-
-        let truncated = self.int / 3u32.pow(rhs.into());
+        self.rotate_right(rhs)
+    }
+}
 
-        Self {
-            int: truncated + wrapped,
-            cells: self.cells,
-        }
+impl ShrAssign<u8> for Ring {
+    fn shr_assign(&mut self, rhs: u8) {
+        *self = self.rotate_right(rhs);
     }
 }
 
 impl Hash for Ring {
     fn hash<H: Hasher>(&self, state: &mut H) {
-        state.write_u32(self.canonicalize().int);
+        state.write_u128(self.canonicalize().int);
     }
 }
 
 impl PartialEq for Ring {
     fn eq(&self, other: &Self) -> bool {
-        self.canonicalize().int == other.canonicalize().int
+        // The number of cells matters too, otherwise all the empty rings would be equal no matter how big they were.
+        self.cells == other.cells && self.canonicalize().int == other.canonicalize().int
     }
 }
 
 impl Eq for Ring {}
 
+/// Rings are ordered by their size first, and then by the internal integers of their canonical forms.
+/// Like `PartialEq`, this means that rotations and reflections of a ring are neither more nor less than it.
+impl PartialOrd for Ring {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Ring {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.cells
+            .cmp(&other.cells)
+            .then_with(|| self.canonicalize().int.cmp(&other.canonicalize().int))
+    }
+}
+
+/// A wrapper around `Ring` whose `PartialEq` and `Hash` only consider rings equal if they have exactly the same cells,
+/// rather than also treating rotations and reflections as equal like `Ring` does.
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct Literal(pub Ring);
+
+impl Hash for Literal {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_u128(self.0.int);
+        state.write_u8(self.0.cells);
+    }
+}
+
+impl PartialEq for Literal {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.int == other.0.int && self.0.cells == other.0.cells
+    }
+}
+
+impl Eq for Literal {}
+
+/// A `Ring` along with its canonical form, worked out once up front.
+///
+/// `Ring`'s `PartialEq`, `Hash` and `Ord` have to canonicalize the ring every time they're called, which adds up when
+/// the same rings get hashed and compared over and over, e.g. as keys in a `HashMap`. These behave exactly the same,
+/// except that they just use the stored canonical form.
+#[derive(Debug, Clone, Copy)]
+pub struct CanonicalRing {
+    ring: Ring,
+    canonical: u128,
+}
+
+impl CanonicalRing {
+    pub fn new(ring: Ring) -> Self {
+        Self {
+            ring,
+            canonical: ring.canonicalize().int,
+        }
+    }
+
+    /// The ring this was made from, exactly as it was.
+    pub fn ring(&self) -> Ring {
+        self.ring
+    }
+
+    pub fn canonical(&self) -> Ring {
+        Ring {
+            int: self.canonical,
+            cells: self.ring.cells,
+        }
+    }
+}
+
+impl From<Ring> for CanonicalRing {
+    fn from(ring: Ring) -> Self {
+        Self::new(ring)
+    }
+}
+
+impl Hash for CanonicalRing {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        // This gives the same hash as `Ring` does.
+        state.write_u128(self.canonical);
+    }
+}
+
+impl PartialEq for CanonicalRing {
+    fn eq(&self, other: &Self) -> bool {
+        self.ring.cells == other.ring.cells && self.canonical == other.canonical
+    }
+}
+
+impl Eq for CanonicalRing {}
+
+impl PartialOrd for CanonicalRing {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for CanonicalRing {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.ring
+            .cells
+            .cmp(&other.ring.cells)
+            .then(self.canonical.cmp(&other.canonical))
+    }
+}
+
 impl Debug for Ring {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         <Self as Display>::fmt(self, f)
@@ -258,14 +2423,43 @@ impl Debug for Ring {
 
 impl Display for Ring {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        for cell in self.into_iter() {
-            f.write_char(match cell {
-                Glyph::None => ' ',
-                Glyph::X => 'X',
-                Glyph::O => 'O',
-            })?;
+        self.write_with(f, ' ', 'X', 'O')
+    }
+}
+
+/// Parses either the base-3 digits from `Ring::to_base3` or the glyphs from `Display`, e.g. `"01201201"` or `" XO XO X"`.
+///
+/// If the string is made up of nothing but `0`, `1` and `2`, it's treated as digits; otherwise it has to be all glyphs.
+impl TryFrom<&str> for Ring {
+    type Error = ParseRingError;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        if s.chars().all(|c| matches!(c, '0' | '1' | '2')) {
+            Self::from_base3(s)
+        } else {
+            Self::parse_with(s, ' ', 'X', 'O')
         }
-        Ok(())
+    }
+}
+
+/// The inverse of `Display`: parses one character per cell, where `' '` is an empty cell and `X` and `O` are themselves,
+/// e.g. `" XO XO X"`.
+///
+/// Unlike `TryFrom<&str>`, this doesn't accept base-3 digits.
+impl FromStr for Ring {
+    type Err = ParseRingError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse_with(s, ' ', 'X', 'O')
+    }
+}
+
+/// Panics if `N` is 0 or more than `Ring::MAX_CELLS`.
+impl<const N: usize> From<[Glyph; N]> for Ring {
+    fn from(cells: [Glyph; N]) -> Self {
+        assert!(N > 0, "a ring needs at least one cell");
+        assert!(N <= Ring::MAX_CELLS.into(), "too many cells");
+        cells.iter().copied().collect()
     }
 }
 
@@ -283,11 +2477,38 @@ impl FromIterator<Glyph> for Ring {
                 Glyph::O => 2,
             }
         }
-        debug_assert!(cells <= 20);
+        debug_assert!(cells <= Ring::MAX_CELLS);
         Self { int, cells }
     }
 }
 
+/// Any ring of any size up to `Ring::MAX_CELLS`.
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for Ring {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        let cells = u.int_in_range(1..=Ring::MAX_CELLS)?;
+        // Picking the integer directly rather than each cell means every ring of this size is equally likely.
+        let int = u.int_in_range(0..=3u128.pow(cells.into()) - 1)?;
+        Ok(Self { int, cells })
+    }
+}
+
+/// Rings are serialized as their `Display` form, e.g. `"XO  X O "`, which gets checked when they're deserialized.
+#[cfg(feature = "serde")]
+impl serde::Serialize for Ring {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Ring {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
 impl IntoIterator for Ring {
     type Item = Glyph;
 
@@ -296,63 +2517,112 @@ impl IntoIterator for Ring {
     fn into_iter(self) -> Self::IntoIter {
         Cells {
             int: self.int,
-            denom: 3u32.pow((self.cells - 1).into()),
+            denom: 3u128.pow((self.cells - 1).into()),
+            remaining: self.cells.into(),
         }
     }
 }
 
+impl IntoIterator for &Ring {
+    type Item = Glyph;
+
+    type IntoIter = Cells;
+
+    fn into_iter(self) -> Self::IntoIter {
+        (*self).into_iter()
+    }
+}
+
 #[derive(Clone)]
 pub struct Cells {
-    int: u32,
-    denom: u32,
+    int: u128,
+    denom: u128,
+    /// The number of cells left to go, from either end.
+    remaining: usize,
 }
 
 impl Iterator for Cells {
     type Item = Glyph;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.denom == 0 {
-            None
-        } else {
-            let digit = self.int / self.denom % 3;
-            self.int %= self.denom;
-            self.denom /= 3;
+        if self.remaining == 0 {
+            return None;
+        }
 
-            Some(Glyph::from_digit(digit))
+        let digit = self.int / self.denom % 3;
+        self.int %= self.denom;
+        self.denom /= 3;
+        self.remaining -= 1;
+
+        Some(Glyph::from_digit(digit))
+    }
+
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        if n >= self.remaining {
+            self.denom = 0;
+            self.remaining = 0;
+            return None;
         }
+
+        // Skip straight past the first `n` digits, rather than going through them one at a time.
+        // `n` is less than the number of cells, so this can't overflow.
+        self.denom /= 3u128.pow(n as u32);
+        self.int %= self.denom * 3;
+        self.remaining -= n;
+
+        self.next()
     }
 
     fn size_hint(&self) -> (usize, Option<usize>) {
-        let len = self.len();
-        (len, Some(len))
+        (self.remaining, Some(self.remaining))
+    }
+
+    fn count(self) -> usize {
+        self.remaining
+    }
+
+    fn last(mut self) -> Option<Self::Item> {
+        // The last cell is the least significant digit, so there's no need to go through all the others to get to it.
+        self.next_back()
     }
 }
 
+// Once `remaining` reaches 0, it stays there.
+impl FusedIterator for Cells {}
+
 impl DoubleEndedIterator for Cells {
     fn next_back(&mut self) -> Option<Self::Item> {
-        if self.denom == 0 {
-            None
-        } else {
-            let digit = self.int % 3;
-            self.int /= 3;
-            self.denom /= 3;
+        if self.remaining == 0 {
+            return None;
+        }
+
+        let digit = self.int % 3;
+        self.int /= 3;
+        self.denom /= 3;
+        self.remaining -= 1;
+
+        Some(Glyph::from_digit(digit))
+    }
 
-            Some(Glyph::from_digit(digit))
+    fn nth_back(&mut self, n: usize) -> Option<Self::Item> {
+        if n >= self.remaining {
+            self.denom = 0;
+            self.remaining = 0;
+            return None;
         }
+
+        // Chop off the last `n` digits all at once, the same as `nth` does from the front.
+        let skipped = 3u128.pow(n as u32);
+        self.int /= skipped;
+        self.denom /= skipped;
+        self.remaining -= n;
+
+        self.next_back()
     }
 }
 
 impl ExactSizeIterator for Cells {
     fn len(&self) -> usize {
-        // There's no log for ints, so just count the number of times we have to divide `denom` by 3 for it to reach 0.
-        // (This might actually be less efficient than converting to a float for all I know, but that just seems kinda icky.)
-
-        let mut denom = self.denom;
-        let mut count = 0;
-        while denom > 0 {
-            denom /= 3;
-            count += 1;
-        }
-        count
+        self.remaining
     }
 }
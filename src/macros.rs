@@ -0,0 +1,28 @@
+//! Macros for writing out rings and boards literally, mostly for tests.
+
+/// Make a `Ring` from a string in the same form as its `Display`, e.g. `ring!("XO  X O ")`.
+///
+/// The string gets checked at compile time, so a typo is a compile error rather than a panic.
+#[macro_export]
+macro_rules! ring {
+    ($ring:expr) => {{
+        const RING: $crate::Ring = $crate::Ring::__parse_const($ring);
+        RING
+    }};
+}
+
+/// Make a `Board` from its center and a string for its ring (see `ring!`), e.g. `board!(center: X, ring: "XO  X O ")`.
+///
+/// The center can be left out to leave it empty.
+#[macro_export]
+macro_rules! board {
+    (center: $center:ident, ring: $ring:expr $(,)?) => {
+        $crate::Board {
+            center: $crate::Glyph::$center,
+            ring: $crate::ring!($ring),
+        }
+    };
+    (ring: $ring:expr $(,)?) => {
+        $crate::board!(center: None, ring: $ring)
+    };
+}
@@ -1,11 +1,22 @@
+use std::env;
 use std::f32::consts::FRAC_1_SQRT_2;
-use std::f32::consts::PI;
 use std::f32::consts::TAU;
+use std::fs;
+use std::process;
 
 use macroquad::prelude::*;
+use ringtactoe::game::Game;
+use ringtactoe::geometry;
+use ringtactoe::geometry::Layout;
 use ringtactoe::Board;
+use ringtactoe::GameStatus;
 use ringtactoe::Glyph;
+use ringtactoe::Move;
+use ringtactoe::Player;
+use ringtactoe::Position;
 use ringtactoe::Win;
+use serde::Deserialize;
+use serde::Serialize;
 
 const RADIUS: f32 = 300.0;
 const CENTER_RADIUS: f32 = 100.0;
@@ -25,14 +36,73 @@ const OUTER_GAP_ANGLE: f32 = GAP / RADIUS;
 const LINE_INNER_RADIUS: f32 = RING_INNER_RADIUS + RING_THICKNESS / 2.0 - WIN_LINE_THICKNESS / 2.0;
 const LINE_OUTER_RADIUS: f32 = RING_INNER_RADIUS + RING_THICKNESS / 2.0 + WIN_LINE_THICKNESS / 2.0;
 const LINE_INNER_GAP_ANGLE: f32 = GAP / LINE_INNER_RADIUS;
-const LINE_OUTER_GAP_ANGLE: f32 = GAP / LINE_OUTER_RADIUS;
 
-const SURFACE_COLOR: Color = LIME;
-const GLYPH_COLOR: Color = WHITE;
+const LAYOUT: Layout = Layout {
+    center_radius: CENTER_RADIUS,
+    ring_inner_radius: RING_INNER_RADIUS,
+    ring_outer_radius: RADIUS,
+};
+
+/// The colors and line thicknesses to draw everything with.
+struct Theme {
+    background: Color,
+    surface: Color,
+    glyph: Color,
+    /// The color of text drawn straight onto the background.
+    text: Color,
+    win_line: Color,
+    line_thickness: f32,
+    win_line_thickness: f32,
+}
+
+const NORMAL_THEME: Theme = Theme {
+    background: BLACK,
+    surface: LIME,
+    glyph: WHITE,
+    text: WHITE,
+    win_line: RED,
+    line_thickness: LINE_THICKNESS,
+    win_line_thickness: WIN_LINE_THICKNESS,
+};
+
+/// A theme for people who find the normal one hard to read: black on yellow, with thicker lines everywhere.
+const HIGH_CONTRAST_THEME: Theme = Theme {
+    background: BLACK,
+    surface: YELLOW,
+    glyph: BLACK,
+    text: YELLOW,
+    win_line: Color::new(0.0, 0.2, 1.0, 1.0),
+    line_thickness: LINE_THICKNESS * 2.0,
+    win_line_thickness: WIN_LINE_THICKNESS * 2.0,
+};
+
+fn theme(high_contrast: bool) -> &'static Theme {
+    if high_contrast {
+        &HIGH_CONTRAST_THEME
+    } else {
+        &NORMAL_THEME
+    }
+}
 
 const MOVEMENT_THRESHOLD: f32 = 5.0;
 
-fn draw_glyph(x: f32, y: f32, rotation: f32, radius: f32, glyph: Glyph) {
+const FONT_SIZE: f32 = 40.0;
+
+/// Where the game gets saved to and loaded from.
+const SAVE_PATH: &str = "ringtactoe.json";
+
+/// How long messages like "Game saved" stay on the screen, in seconds.
+const MESSAGE_DURATION: f64 = 3.0;
+
+fn draw_glyph(
+    x: f32,
+    y: f32,
+    rotation: f32,
+    radius: f32,
+    glyph: Glyph,
+    color: Color,
+    thickness: f32,
+) {
     match glyph {
         Glyph::None => {}
         Glyph::X => {
@@ -44,26 +114,12 @@ fn draw_glyph(x: f32, y: f32, rotation: f32, radius: f32, glyph: Glyph) {
             let off1 = radius * (sin + cos) * FRAC_1_SQRT_2;
             let off2 = radius * (sin - cos) * FRAC_1_SQRT_2;
 
-            draw_line(
-                x - off1,
-                y - off2,
-                x + off1,
-                y + off2,
-                LINE_THICKNESS,
-                GLYPH_COLOR,
-            );
+            draw_line(x - off1, y - off2, x + off1, y + off2, thickness, color);
 
-            draw_line(
-                x + off2,
-                y - off1,
-                x - off2,
-                y + off1,
-                LINE_THICKNESS,
-                GLYPH_COLOR,
-            );
+            draw_line(x + off2, y - off1, x - off2, y + off1, thickness, color);
         }
         Glyph::O => {
-            draw_poly_lines(x, y, 100, radius, rotation, LINE_THICKNESS, GLYPH_COLOR);
+            draw_poly_lines(x, y, 100, radius, rotation, thickness, color);
         }
     }
 }
@@ -114,24 +170,72 @@ fn draw_arc(
     }
 }
 
-fn draw_board(board: &Board, rotation: f32) {
-    let glyph_radius = f32::min(
-        LINE_INNER_RADIUS * (TAU / board.ring.len() as f32 - LINE_INNER_GAP_ANGLE) / 2.0 - GAP,
+fn glyph_radius(ring_size: u8) -> f32 {
+    f32::min(
+        LINE_INNER_RADIUS * (TAU / ring_size as f32 - LINE_INNER_GAP_ANGLE) / 2.0 - GAP,
         CENTER_RADIUS * 2.0 / 3.0,
-    );
+    )
+}
+
+/// Draw a see-through version of `glyph` at `mv`, to show what'll happen if it's played there.
+fn draw_preview(board: &Board, rotation: f32, mv: Position, player: Player, theme: &Theme) {
+    let color = Color {
+        a: 0.5,
+        ..theme.glyph
+    };
+
+    let center_x = screen_width() / 2.0;
+    let center_y = screen_height() / 2.0;
+    let glyph_radius = glyph_radius(board.ring.len());
+
+    match mv {
+        Position::Center => draw_glyph(
+            center_x,
+            center_y,
+            0.0,
+            glyph_radius,
+            player.glyph(),
+            color,
+            theme.line_thickness,
+        ),
+        Position::Ring(i) => {
+            let angle = geometry::index_to_angle(i, board.ring.len(), rotation);
+            draw_glyph(
+                center_x + LINE_OUTER_RADIUS * angle.cos(),
+                center_y + LINE_OUTER_RADIUS * angle.sin(),
+                angle,
+                glyph_radius,
+                player.glyph(),
+                color,
+                theme.line_thickness,
+            );
+        }
+    }
+}
+
+fn draw_board(board: &Board, rotation: f32, theme: &Theme) {
+    let glyph_radius = glyph_radius(board.ring.len());
 
     let center_x = screen_width() / 2.0;
     let center_y = screen_height() / 2.0;
 
     // First, just draw the middle.
-    draw_poly(center_x, center_y, 100, CENTER_RADIUS, 0.0, SURFACE_COLOR);
-    draw_glyph(center_x, center_y, 0.0, glyph_radius, board.center);
+    draw_poly(center_x, center_y, 100, CENTER_RADIUS, 0.0, theme.surface);
+    draw_glyph(
+        center_x,
+        center_y,
+        0.0,
+        glyph_radius,
+        board.center,
+        theme.glyph,
+        theme.line_thickness,
+    );
 
     // Drawing the ring around the outside is a bit more complicated, since macroquad doesn't provide any way of drawing arcs or anything.
     // So instead, we just have to draw all the individual triangles ourselves.
-    for (i, glyph) in board.ring.into_iter().enumerate() {
+    for (i, glyph) in board.ring.iter().enumerate() {
         let ring_size = board.ring.len() as f32;
-        let angle = rotation + i as f32 / ring_size * TAU;
+        let angle = geometry::index_to_angle(i as u8, board.ring.len(), rotation);
         let arc = TAU / ring_size;
         let inner_arc = arc - INNER_GAP_ANGLE;
         let outer_arc = arc - OUTER_GAP_ANGLE;
@@ -142,7 +246,7 @@ fn draw_board(board: &Board, rotation: f32) {
             outer_arc,
             CENTER_RADIUS + GAP,
             RADIUS,
-            SURFACE_COLOR,
+            theme.surface,
         );
 
         draw_glyph(
@@ -151,53 +255,160 @@ fn draw_board(board: &Board, rotation: f32) {
             angle,
             glyph_radius,
             glyph,
+            theme.glyph,
+            theme.line_thickness,
         );
     }
 
-    for win in board.wins() {
+    for win in board.wins_merged() {
         match win {
-            Win::Center { index } => {
-                let ring_size = board.ring.len() as f32;
-
-                let angle = rotation + index as f32 / ring_size * TAU;
-
-                let x_off = RADIUS * angle.cos();
-                let y_off = RADIUS * angle.sin();
-
-                draw_line(
-                    center_x - x_off,
-                    center_y - y_off,
-                    center_x + x_off,
-                    center_y + y_off,
-                    WIN_LINE_THICKNESS,
-                    RED,
-                );
+            Win::Center { index, .. } => {
+                let ring_len = board.ring.len();
+
+                // On odd rings the other end isn't quite opposite, so draw the line in two halves meeting at the center.
+                for i in [index, (index + ring_len / 2) % ring_len] {
+                    let angle = geometry::index_to_angle(i, ring_len, rotation);
+                    draw_line(
+                        center_x,
+                        center_y,
+                        center_x + RADIUS * angle.cos(),
+                        center_y + RADIUS * angle.sin(),
+                        theme.win_line_thickness,
+                        theme.win_line,
+                    );
+                }
             }
-            Win::Ring { index } => {
+            Win::Ring { index, length, .. } => {
                 let ring_size = board.ring.len() as f32;
 
-                let angle = rotation + (index + 1) as f32 / ring_size * TAU;
-                let inner_arc = TAU / ring_size * 3.0 - LINE_INNER_GAP_ANGLE;
-                let outer_arc = TAU / ring_size * 3.0 - LINE_OUTER_GAP_ANGLE;
+                // Point the arc at the middle of the run.
+                let angle = rotation + (index as f32 + (length - 1) as f32 / 2.0) / ring_size * TAU;
+                // The line runs along the middle of the ring, however thick it is.
+                let middle = RING_INNER_RADIUS + RING_THICKNESS / 2.0;
+                let inner_radius = middle - theme.win_line_thickness / 2.0;
+                let outer_radius = middle + theme.win_line_thickness / 2.0;
+                let inner_arc = TAU / ring_size * length as f32 - GAP / inner_radius;
+                let outer_arc = TAU / ring_size * length as f32 - GAP / outer_radius;
 
                 draw_arc(
                     angle,
                     inner_arc,
                     outer_arc,
-                    LINE_INNER_RADIUS,
-                    LINE_OUTER_RADIUS,
-                    RED,
+                    inner_radius,
+                    outer_radius,
+                    theme.win_line,
                 );
             }
         }
     }
 }
 
+fn draw_centered_text(text: &str, y: f32, theme: &Theme) {
+    let dimensions = measure_text(text, None, FONT_SIZE as u16, 1.0);
+    draw_text(
+        text,
+        (screen_width() - dimensions.width) / 2.0,
+        y,
+        FONT_SIZE,
+        theme.text,
+    );
+}
+
+/// What gets written to `SAVE_PATH`.
+#[derive(Serialize, Deserialize)]
+struct SaveFile {
+    /// The board the game started from, in the same format as `Board`'s `Display` impl. This isn't always empty,
+    /// since the game might have been started from a position code.
+    start: String,
+    /// Every move made since then, so that they can still be undone after loading.
+    history: Vec<Move>,
+}
+
+/// Save `game`, including its history. Moves which have been undone aren't saved, so they can't be redone after
+/// loading.
+fn save(game: &Game) -> Result<(), String> {
+    let mut start = game.clone();
+    while start.undo().is_some() {}
+    let save = SaveFile {
+        start: start.board().to_string(),
+        history: game.history().to_vec(),
+    };
+
+    let json = serde_json::to_string_pretty(&save).map_err(|err| err.to_string())?;
+    fs::write(SAVE_PATH, json).map_err(|err| err.to_string())
+}
+
+fn load() -> Result<Game, String> {
+    let json = fs::read_to_string(SAVE_PATH).map_err(|err| err.to_string())?;
+    let save: SaveFile = serde_json::from_str(&json).map_err(|err| err.to_string())?;
+
+    let start = save.start.parse::<Board>().map_err(|err| err.to_string())?;
+    // The board doesn't say whose turn it is, but `Game` can work it out from the glyphs.
+    let mut game = Game::from_board(start);
+    for mv in save.history {
+        game.apply(mv).map_err(|err| err.to_string())?;
+    }
+    Ok(game)
+}
+
+/// Ask the player who they want to play as, and return which player the AI should be (or `None` if there's no AI).
+///
+/// High contrast mode can be toggled from here too, so that people who need it can read the menu.
+async fn pick_ai(high_contrast: &mut bool) -> Option<Player> {
+    loop {
+        if is_key_pressed(KeyCode::H) {
+            *high_contrast = !*high_contrast;
+        }
+
+        let theme = theme(*high_contrast);
+        clear_background(theme.background);
+
+        let center_y = screen_height() / 2.0;
+        draw_centered_text(
+            "Press 1 to play as X (going first)",
+            center_y - FONT_SIZE,
+            theme,
+        );
+        draw_centered_text("Press 2 to play as O (going second)", center_y, theme);
+        draw_centered_text("Press 3 for two players", center_y + FONT_SIZE, theme);
+        draw_centered_text(
+            "Press H to toggle high contrast",
+            center_y + FONT_SIZE * 3.0,
+            theme,
+        );
+        draw_centered_text(
+            "Press U to undo a move and Y to redo it",
+            center_y + FONT_SIZE * 4.0,
+            theme,
+        );
+
+        if is_key_pressed(KeyCode::Key1) {
+            return Some(Player::O);
+        } else if is_key_pressed(KeyCode::Key2) {
+            return Some(Player::X);
+        } else if is_key_pressed(KeyCode::Key3) {
+            return None;
+        }
+
+        next_frame().await;
+    }
+}
+
 #[macroquad::main("Ring-Tac-Toe")]
 async fn main() {
-    let mut board = Board::new(8);
+    // This sticks around between games, so it only needs turning on once.
+    let mut high_contrast = false;
 
-    let mut turn = Glyph::X;
+    let ai = pick_ai(&mut high_contrast).await;
+
+    // A position can be passed in as a code from `Board::to_code`, to carry on from there.
+    let mut game = match env::args().nth(1) {
+        Some(code) => Game::from_board(Board::from_code(&code).unwrap_or_else(|err| {
+            eprintln!("invalid position code: {}", err);
+            process::exit(1);
+        })),
+        None => Game::default(),
+    };
 
     let mut rotation = 0.0;
     let mut velocity = 0.0;
@@ -208,21 +419,121 @@ async fn main() {
     let mut mouse_movement = 0.0;
     let mut last_mouse_pos = (0.0, 0.0);
 
+    // A message to show at the bottom of the screen, and when it was shown.
+    let mut message: Option<(String, f64)> = None;
+
     loop {
-        draw_board(&board, rotation);
+        if is_key_pressed(KeyCode::H) {
+            high_contrast = !high_contrast;
+        }
+
+        let theme = theme(high_contrast);
+        clear_background(theme.background);
+        draw_board(game.board(), rotation, theme);
+
+        let moves_made = game.board().ring.occupied() + game.board().center.is_occupied() as u8;
+        // Once the game's over there's no next move, so just show the last one.
+        let move_number = if !game.is_over() {
+            moves_made + 1
+        } else {
+            moves_made
+        };
+        draw_text(
+            &format!("Move {}", move_number),
+            FONT_SIZE / 2.0,
+            FONT_SIZE,
+            FONT_SIZE,
+            theme.text,
+        );
+
+        let status = match game.status() {
+            GameStatus::InProgress => None,
+            GameStatus::Won(Player::X) => Some("X wins! Press R to play again"),
+            GameStatus::Won(_) => Some("O wins! Press R to play again"),
+            GameStatus::Draw => Some("It's a draw. Press R to play again"),
+        };
+        if let Some(status) = status {
+            draw_centered_text(status, FONT_SIZE * 2.0, theme);
+        }
+
+        if is_key_pressed(KeyCode::R) {
+            // Start a new game, with the same players as before.
+            game.restart();
+        }
+
+        if is_key_pressed(KeyCode::U) {
+            // Take back the AI's reply as well, otherwise it'd just play it again straight away.
+            if game.undo().is_some() && Some(game.turn()) == ai {
+                game.undo();
+            }
+        }
+
+        if is_key_pressed(KeyCode::Y) {
+            // Likewise, redo the AI's reply along with the move it was replying to.
+            if game.redo().is_some() && Some(game.turn()) == ai {
+                game.redo();
+            }
+        }
+
+        if is_key_pressed(KeyCode::S) {
+            let text = match save(&game) {
+                Ok(()) => format!("Saved to {}", SAVE_PATH),
+                Err(err) => format!("Couldn't save: {}", err),
+            };
+            message = Some((text, get_time()));
+        }
+
+        if is_key_pressed(KeyCode::C) {
+            message = Some((
+                format!("Position code: {}", game.board().to_code()),
+                get_time(),
+            ));
+        }
+
+        if is_key_pressed(KeyCode::L) {
+            let text = match load() {
+                Ok(loaded) => {
+                    game = loaded;
+                    rotation = 0.0;
+                    velocity = 0.0;
+                    last_mouse_angle = None;
+                    format!("Loaded from {}", SAVE_PATH)
+                }
+                Err(err) => format!("Couldn't load: {}", err),
+            };
+            message = Some((text, get_time()));
+        }
+
+        if let Some((text, shown_at)) = &message {
+            if get_time() - shown_at < MESSAGE_DURATION {
+                draw_centered_text(text, screen_height() - FONT_SIZE, theme);
+            } else {
+                message = None;
+            }
+        }
+
+        if Some(game.turn()) == ai && !game.is_over() {
+            // This blocks the whole frame while it's thinking, but it's quick enough on the default board size.
+            if let Some(mv) = game.best_move() {
+                game.play(mv).unwrap();
+            }
+        }
 
         let (mut x, mut y) = mouse_position();
         x -= screen_width() / 2.0;
         y -= screen_height() / 2.0;
 
-        // inverse tan is also called arctan, apparently
-        let mut angle = f32::atan(y / x);
-        // `atan` returns angles in the first quadrant for positive values and angles in the fourth quadrant for negative values.
-        // Fix the angle to be in the correct quadrant.
-        if x < 0.0 {
-            // If `angle` is negative, it will end up as PI - angle, in the second quadrant, which is correct.
-            // If `angle` is positive, it will end up as PI + angle, in the third quadrant, which is correct.
-            angle += PI;
+        let angle = geometry::point_angle(x, y);
+        let hovered = LAYOUT.hit_test(x, y, game.board().ring.len(), rotation);
+
+        if let Some(mv) = hovered {
+            let occupied = match mv {
+                Position::Center => game.board().center.is_occupied(),
+                Position::Ring(i) => game.board().ring.get(i).is_occupied(),
+            };
+            if !occupied && Some(game.turn()) != ai && !game.is_over() {
+                draw_preview(game.board(), rotation, mv, game.turn(), theme);
+            }
         }
 
         if let Some(last_angle) = last_mouse_angle {
@@ -231,32 +542,12 @@ async fn main() {
                 last_mouse_angle = None;
 
                 // If the mouse was barely moved, we consider it a click.
-                if mouse_movement < MOVEMENT_THRESHOLD && board.winner() == Glyph::None {
+                if mouse_movement < MOVEMENT_THRESHOLD && Some(game.turn()) != ai {
                     // We already know they were clicking the ring, since `last_mouse_angle` was `Some`.
+                    let i = geometry::angle_to_index(angle, game.board().ring.len(), rotation);
 
-                    // Undo the offset of the ring's rotation
-                    angle -= rotation;
-
-                    // Put all of the angles in the 0..TAU range.
-                    while angle < 0.0 {
-                        angle += TAU;
-                    }
-
-                    angle %= TAU;
-
-                    // Figure out which index in the ring the angle corresponds to.
-                    let i = f32::round(angle / TAU * board.ring.len() as f32) as u8;
-
-                    if board.ring.get(i) == Glyph::None {
-                        // Set the glyph.
-                        board.ring.set(i, turn);
-
-                        turn = match turn {
-                            Glyph::X => Glyph::O,
-                            Glyph::O => Glyph::X,
-                            Glyph::None => unreachable!(),
-                        }
-                    }
+                    // This fails if the cell's already taken or the game's over, in which case we just ignore the click.
+                    let _ = game.play(Position::Ring(i));
                 } else {
                     // This was a drag, so give the ring the velocity that mouse had when it let go.
                     velocity = diff / get_frame_time();
@@ -271,25 +562,17 @@ async fn main() {
                 mouse_movement = 0.0;
                 last_mouse_pos = (x, y);
 
-                let dist_from_center = f32::sqrt(x.powi(2) + y.powi(2));
                 // The click was within the ring, so mark it as grabbed.
-                if dist_from_center > CENTER_RADIUS + GAP && dist_from_center < RADIUS {
+                if LAYOUT.on_ring(x, y) {
                     last_mouse_angle = Some(angle);
                 }
             } else if is_mouse_button_released(MouseButton::Left) {
                 // If the mouse was barely moved, we consider it a click.
-                if mouse_movement < MOVEMENT_THRESHOLD && board.winner() == Glyph::None {
+                if mouse_movement < MOVEMENT_THRESHOLD && Some(game.turn()) != ai {
                     // If this was a click on the ring, `last_mouse_angle` would have been `Some`, so this can only have been a click in the center.
-                    let dist_from_center = f32::sqrt(x.powi(2) + y.powi(2));
-                    if dist_from_center < CENTER_RADIUS && board.center == Glyph::None {
-                        // They clicked the center.
-                        board.center = turn;
-
-                        turn = match turn {
-                            Glyph::X => Glyph::O,
-                            Glyph::O => Glyph::X,
-                            Glyph::None => unreachable!(),
-                        }
+                    // This fails if the center's already taken or the game's over, in which case we just ignore the click.
+                    if hovered == Some(Position::Center) {
+                        let _ = game.play(Position::Center);
                     }
                 }
             }
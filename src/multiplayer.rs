@@ -0,0 +1,386 @@
+//! Games with more than two players, each with a glyph of their own.
+//!
+//! `Ring` packs its cells into base 3, which only leaves room for X and O, so this has its own board, `MultiBoard`,
+//! which just stores which seat has each cell. Apart from that it's laid out exactly like a `Board`, with the same
+//! lines (see `Line`).
+//!
+//! This is a separate backend on purpose, rather than something `Board` and `Game` can do. Everything else in the
+//! crate is built on there being exactly two glyphs packed into a `Ring`: the symmetry-aware equality, the Zobrist
+//! hashes, the byte and code formats, the solver, and every `rules::Variant`. Giving all of those a list of players
+//! would make them slower and more complicated for a mode that doesn't use any of them, so `Game` stays two-player,
+//! and `MultiGame` only plays by the normal rules: no variants, solver, clock or events. It does keep track of the turn
+//! and the history the same way as `Game`, though, with the same `undo`, `redo` and `status`.
+
+use alloc::vec::Vec;
+use core::fmt;
+use core::fmt::Debug;
+use core::fmt::Display;
+use core::fmt::Formatter;
+use core::str::FromStr;
+#[cfg(feature = "std")]
+use std::error::Error;
+
+use crate::BoardError;
+use crate::Line;
+use crate::MoveError;
+use crate::ParseRingError;
+use crate::Position;
+use crate::Ring;
+
+/// How many different glyphs there are for players to use.
+pub const MAX_PLAYERS: u8 = 4;
+
+/// The characters for each seat's glyph, in order.
+const GLYPHS: [char; MAX_PLAYERS as usize] = ['X', 'O', 'Y', 'Z'];
+
+/// One of the players in a `MultiGame`, which also decides their glyph: seat 0 is X, seat 1 is O, seat 2 is Y and seat
+/// 3 is Z.
+///
+/// Every seat has a glyph, since the only way to make one is with `Seat::new` (or `Seat::from_char`), which won't go
+/// past `MAX_PLAYERS`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Seat(u8);
+
+impl Seat {
+    /// Get seat number `index`, or `None` if it's `MAX_PLAYERS` or more and so wouldn't have a glyph.
+    pub fn new(index: u8) -> Option<Seat> {
+        (index < MAX_PLAYERS).then_some(Seat(index))
+    }
+
+    /// Which number seat this is, starting from 0.
+    pub fn index(self) -> u8 {
+        self.0
+    }
+
+    /// Get the character for this seat's glyph.
+    pub fn to_char(self) -> char {
+        GLYPHS[usize::from(self.0)]
+    }
+
+    /// Get the seat whose glyph is `c`.
+    pub fn from_char(c: char) -> Option<Seat> {
+        GLYPHS
+            .iter()
+            .position(|&glyph| glyph == c)
+            .map(|seat| Seat(seat as u8))
+    }
+}
+
+/// A board where each cell is either empty or has one of up to `MAX_PLAYERS` players' glyphs in it.
+///
+/// Unlike `Board`, this compares boards literally, cell by cell: rotations and reflections of a board aren't equal to
+/// it, and don't hash the same either.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MultiBoard {
+    pub center: Option<Seat>,
+    /// Only the first `cells` of these are actually part of the ring; the rest are always `None`.
+    ring: [Option<Seat>; Ring::MAX_CELLS as usize],
+    cells: u8,
+}
+
+impl MultiBoard {
+    /// Create a new, blank board with `cells` around the outside.
+    pub fn new(cells: u8) -> Result<Self, BoardError> {
+        BoardError::check_cells(cells)?;
+        Ok(Self {
+            center: None,
+            ring: [None; Ring::MAX_CELLS as usize],
+            cells,
+        })
+    }
+
+    /// The number of cells in the ring.
+    pub fn ring_len(&self) -> u8 {
+        self.cells
+    }
+
+    /// Get whose glyph is at `pos`, if anyone's.
+    ///
+    /// Panics if `pos` is past the end of the ring.
+    pub fn get(&self, pos: Position) -> Option<Seat> {
+        match pos {
+            Position::Center => self.center,
+            Position::Ring(i) => self.ring[usize::from(self.check(i))],
+        }
+    }
+
+    /// Put `seat`'s glyph at `pos`, or empty it if `seat` is `None`.
+    ///
+    /// Panics if `pos` is past the end of the ring.
+    pub fn set(&mut self, pos: Position, seat: Option<Seat>) {
+        match pos {
+            Position::Center => self.center = seat,
+            Position::Ring(i) => self.ring[usize::from(self.check(i))] = seat,
+        }
+    }
+
+    fn check(&self, i: u8) -> u8 {
+        assert!(i < self.cells, "index out of range");
+        i
+    }
+
+    /// Iterate over every cell on the board and whose glyph is in it, starting with the center and then going around
+    /// the ring.
+    pub fn cells(&self) -> impl Iterator<Item = (Position, Option<Seat>)> + '_ {
+        let ring = (0..self.cells).map(move |i| (Position::Ring(i), self.ring[usize::from(i)]));
+        core::iter::once((Position::Center, self.center)).chain(ring)
+    }
+
+    /// Whether every cell on the board, including the center, has a glyph in it.
+    pub fn is_full(&self) -> bool {
+        self.cells().all(|(_, seat)| seat.is_some())
+    }
+
+    /// Get all of the lines which one player has filled.
+    pub fn wins(&self) -> Vec<Line> {
+        Line::all(self.cells)
+            .filter(|&line| {
+                let [first, rest @ ..] = line.cells(self.cells).map(|pos| self.get(pos));
+                first.is_some() && rest.iter().all(|&seat| seat == first)
+            })
+            .collect()
+    }
+
+    /// Get whoever's filled a line, or `None` if nobody has yet.
+    pub fn winner(&self) -> Option<Seat> {
+        let line = *self.wins().first()?;
+        self.get(line.cells(self.cells)[0])
+    }
+}
+
+/// This uses the same format as `Board`, with a `|` between the center and the ring, and a space for each empty cell.
+impl Display for MultiBoard {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for (pos, seat) in self.cells() {
+            let c = match seat {
+                Some(seat) => seat.to_char(),
+                None => ' ',
+            };
+            write!(f, "{}", c)?;
+            if pos == Position::Center {
+                write!(f, "|")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Debug for MultiBoard {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "\"{}\"", self)
+    }
+}
+
+impl FromStr for MultiBoard {
+    type Err = ParseRingError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parse = |c| match c {
+            ' ' => Ok(None),
+            c => Seat::from_char(c)
+                .map(Some)
+                .ok_or(ParseRingError::InvalidChar(c)),
+        };
+
+        let mut chars = s.chars();
+        let center = match (chars.next(), chars.next()) {
+            (Some(center), Some('|')) => parse(center)?,
+            _ => return Err(ParseRingError::NoCenter),
+        };
+        let ring = chars.as_str();
+        let cells = ring.chars().count();
+        if cells == 0 {
+            return Err(ParseRingError::Empty);
+        }
+        if cells > usize::from(Ring::MAX_CELLS) {
+            return Err(ParseRingError::TooLong(cells));
+        }
+
+        let mut board = Self::new(cells as u8).expect("already checked the number of cells");
+        board.center = center;
+        for (i, c) in ring.chars().enumerate() {
+            board.ring[i] = parse(c)?;
+        }
+        Ok(board)
+    }
+}
+
+/// Whether a `MultiGame` is still going, and if not, how it ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MultiStatus {
+    InProgress,
+    /// This player filled a line.
+    Won(Seat),
+    /// The board filled up without anyone filling a line.
+    Draw,
+}
+
+/// The reasons why a `MultiGame` might not be able to be set up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SetupError {
+    /// The board can't have that many cells.
+    Board(BoardError),
+    /// The list of players was empty.
+    NoPlayers,
+    /// There was only this one player, and a game needs at least two.
+    TooFewPlayers(Seat),
+    /// This seat came up more than once in the list of players.
+    Duplicate(Seat),
+}
+
+impl Display for SetupError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Board(err) => write!(f, "{}", err),
+            Self::NoPlayers => write!(f, "nobody's playing; a game needs at least two players"),
+            Self::TooFewPlayers(seat) => write!(
+                f,
+                "seat {} can't play on their own; a game needs at least two players",
+                seat.index()
+            ),
+            Self::Duplicate(seat) => write!(f, "seat {} is playing more than once", seat.index()),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl Error for SetupError {}
+
+/// A game between anywhere from 2 to `MAX_PLAYERS` players, who take turns in a fixed order.
+#[derive(Debug, Clone)]
+pub struct MultiGame {
+    board: MultiBoard,
+    /// Who's playing, in the order they take turns.
+    players: Vec<Seat>,
+    /// The index in `players` of whoever's turn it is.
+    turn: usize,
+    /// Every move made so far, oldest first.
+    history: Vec<Position>,
+    /// Moves which have been undone, most recently undone last, so that they can be redone.
+    undone: Vec<Position>,
+}
+
+impl MultiGame {
+    /// Start a new game on a blank board with `cells` around the outside, where `players` take turns in that order.
+    ///
+    /// There have to be at least two players, and none of them can be in the list more than once.
+    pub fn new(cells: u8, players: &[Seat]) -> Result<Self, SetupError> {
+        let board = MultiBoard::new(cells).map_err(SetupError::Board)?;
+        match *players {
+            [] => return Err(SetupError::NoPlayers),
+            [seat] => return Err(SetupError::TooFewPlayers(seat)),
+            _ => {}
+        }
+        for (i, &seat) in players.iter().enumerate() {
+            if players[..i].contains(&seat) {
+                return Err(SetupError::Duplicate(seat));
+            }
+        }
+
+        Ok(Self {
+            board,
+            players: players.to_vec(),
+            turn: 0,
+            history: Vec::new(),
+            undone: Vec::new(),
+        })
+    }
+
+    pub fn board(&self) -> &MultiBoard {
+        &self.board
+    }
+
+    /// Who's playing, in the order they take turns.
+    pub fn players(&self) -> &[Seat] {
+        &self.players
+    }
+
+    /// Whose turn it is. Once the game's over, this is whoever would have gone next.
+    pub fn turn(&self) -> Seat {
+        self.players[self.turn]
+    }
+
+    /// The cells that have been played in so far, oldest first, not including any which have been undone.
+    pub fn history(&self) -> &[Position] {
+        &self.history
+    }
+
+    /// Whether the game is still going, and if not, how it ended.
+    pub fn status(&self) -> MultiStatus {
+        match self.board.winner() {
+            Some(seat) => MultiStatus::Won(seat),
+            None if self.board.is_full() => MultiStatus::Draw,
+            None => MultiStatus::InProgress,
+        }
+    }
+
+    /// Whether the game has finished, either with a win or a draw.
+    pub fn is_over(&self) -> bool {
+        self.status() != MultiStatus::InProgress
+    }
+
+    /// Put the glyph of whoever's turn it is at `pos`, and pass the turn on to the next player.
+    ///
+    /// This starts a new line of play, so any moves which were undone can't be redone anymore.
+    pub fn play(&mut self, pos: Position) -> Result<(), MoveError> {
+        self.make(pos)?;
+        self.undone.clear();
+        Ok(())
+    }
+
+    /// Take back the last move, giving the turn back to whoever made it.
+    ///
+    /// Returns where the move was, or `None` if there weren't any moves to take back.
+    pub fn undo(&mut self) -> Option<Position> {
+        let pos = self.history.pop()?;
+        self.board.set(pos, None);
+        self.turn = (self.turn + self.players.len() - 1) % self.players.len();
+        self.undone.push(pos);
+        Some(pos)
+    }
+
+    /// Make the last move that was taken back with `undo` again.
+    ///
+    /// Returns where the move was, or `None` if there's nothing to redo, either because nothing's been undone or
+    /// because a different move has been played since.
+    pub fn redo(&mut self) -> Option<Position> {
+        let pos = self.undone.pop()?;
+        self.make(pos)
+            .expect("undone moves should still be legal after undoing");
+        Some(pos)
+    }
+
+    /// Empty the board and start again from the first player's turn, keeping the same players and number of cells.
+    ///
+    /// This forgets the whole history, so none of it can be undone or redone.
+    pub fn restart(&mut self) {
+        self.board =
+            MultiBoard::new(self.board.ring_len()).expect("the board already had this many cells");
+        self.turn = 0;
+        self.history.clear();
+        self.undone.clear();
+    }
+
+    /// The guts of `play`, without touching the moves that can be redone.
+    fn make(&mut self, pos: Position) -> Result<(), MoveError> {
+        if self.is_over() {
+            return Err(MoveError::GameOver);
+        }
+        if let Position::Ring(index) = pos {
+            if index >= self.board.ring_len() {
+                return Err(MoveError::OutOfRange {
+                    index,
+                    cells: self.board.ring_len(),
+                });
+            }
+        }
+        if self.board.get(pos).is_some() {
+            return Err(MoveError::Occupied);
+        }
+
+        self.board.set(pos, Some(self.turn()));
+        self.history.push(pos);
+        self.turn = (self.turn + 1) % self.players.len();
+        Ok(())
+    }
+}
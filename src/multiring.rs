@@ -0,0 +1,151 @@
+//! A bigger variant of the game, with several concentric rings around the center.
+//!
+//! Each ring has to be a multiple of the size of the ring inside it, so that every cell of the innermost ring has a cell
+//! lined up with it in each of the outer rings. For example, with an inner ring of 8 cells and an outer ring of 16,
+//! inner cell `i` lines up with outer cell `2 * i`.
+//!
+//! There are two kinds of winning line:
+//! - Three cells in a row around any one of the rings, just like on a normal `Board`.
+//! - Three cells in a row along a spoke. A spoke is a straight line through the center, which starts at a cell of the
+//!   innermost ring and goes through the cells lined up with it in the outer rings, as well as the cells on the opposite
+//!   side. So with two rings, a spoke is outer, inner, center, inner, outer, and any three of those in a row count.
+//!
+//! With only one ring this is exactly the same as a normal `Board`.
+
+use alloc::vec::Vec;
+use core::convert::TryInto;
+
+use crate::BoardError;
+use crate::Glyph;
+use crate::Line;
+use crate::Player;
+use crate::Ring;
+
+/// A cell on a `MultiRing`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Place {
+    Center,
+    /// Cell `index` of ring number `ring`, where ring 0 is the innermost.
+    Ring {
+        ring: u8,
+        index: u8,
+    },
+}
+
+pub struct MultiRing {
+    pub center: Glyph,
+    /// The rings, from the innermost outwards.
+    pub rings: Vec<Ring>,
+}
+
+impl MultiRing {
+    /// Create a new, blank board with rings of the given sizes, from the innermost outwards.
+    pub fn new(sizes: &[u8]) -> Result<Self, BoardError> {
+        if sizes.is_empty() {
+            return Err(BoardError::NoCells);
+        }
+
+        for &size in sizes {
+            BoardError::check_cells(size)?;
+        }
+
+        // Spokes go straight through the center, so they need a cell directly opposite.
+        // The outer rings are multiples of this one, so they're always even too.
+        if !sizes[0].is_multiple_of(2) {
+            return Err(BoardError::OddCells(sizes[0]));
+        }
+
+        for pair in sizes.windows(2) {
+            let (inner, outer) = (pair[0], pair[1]);
+            if outer <= inner || !outer.is_multiple_of(inner) {
+                return Err(BoardError::MisalignedRings { inner, outer });
+            }
+        }
+
+        Ok(Self {
+            center: Glyph::None,
+            rings: sizes.iter().map(|&size| Ring::new(size)).collect(),
+        })
+    }
+
+    pub fn get(&self, place: Place) -> Glyph {
+        match place {
+            Place::Center => self.center,
+            Place::Ring { ring, index } => self.rings[usize::from(ring)].get(index),
+        }
+    }
+
+    pub fn set(&mut self, place: Place, glyph: Glyph) {
+        match place {
+            Place::Center => self.center = glyph,
+            Place::Ring { ring, index } => self.rings[usize::from(ring)].set(index, glyph),
+        }
+    }
+
+    /// Get every line of three cells which would win the game if one player filled it.
+    pub fn lines(&self) -> Vec<[Place; 3]> {
+        let mut out = Vec::new();
+
+        for (ring, cells) in self.rings.iter().enumerate() {
+            let ring = ring.try_into().expect("too many rings");
+            let len = cells.len();
+            for i in 0..Line::ring_count(len) {
+                out.push([
+                    Place::Ring { ring, index: i },
+                    Place::Ring {
+                        ring,
+                        index: (i + 1) % len,
+                    },
+                    Place::Ring {
+                        ring,
+                        index: (i + 2) % len,
+                    },
+                ]);
+            }
+        }
+
+        let inner_len = self.rings[0].len();
+        for i in 0..inner_len / 2 {
+            // Walk along the spoke from the outside of one side to the outside of the other.
+            let opposite = i + inner_len / 2;
+            let mut spoke: Vec<_> = self.spoke(opposite).rev().collect();
+            spoke.push(Place::Center);
+            spoke.extend(self.spoke(i));
+
+            for window in spoke.windows(3) {
+                out.push([window[0], window[1], window[2]]);
+            }
+        }
+
+        out
+    }
+
+    /// The cells lined up with cell `i` of the innermost ring, from the inside out.
+    fn spoke(&self, i: u8) -> impl DoubleEndedIterator<Item = Place> + '_ {
+        let inner_len = self.rings[0].len();
+        self.rings
+            .iter()
+            .enumerate()
+            .map(move |(ring, cells)| Place::Ring {
+                ring: ring.try_into().expect("too many rings"),
+                index: i * (cells.len() / inner_len),
+            })
+    }
+
+    /// Get all of the lines which one player has filled.
+    pub fn wins(&self) -> Vec<[Place; 3]> {
+        self.lines()
+            .into_iter()
+            .filter(|line| {
+                let glyph = self.get(line[0]);
+                glyph.is_occupied() && line.iter().all(|&place| self.get(place) == glyph)
+            })
+            .collect()
+    }
+
+    pub fn winner(&self) -> Option<Player> {
+        self.wins()
+            .first()
+            .and_then(|line| self.get(line[0]).player())
+    }
+}
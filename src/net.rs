@@ -0,0 +1,269 @@
+//! Playing a game against somebody else over the network.
+//!
+//! One side is the `Host`, which keeps track of the real board and plays as X, and the other is the `Client`,
+//! which connects to it and plays as O. Everything's sent as lines of text over a TCP connection:
+//!
+//! - `game <cells>`: sent by the host as soon as the client connects, to say how big the ring is.
+//! - `move <move>`: sent by the client to make a move, where `<move>` is either `c` for the center or a ring index.
+//! - `state <center> <ring>`: sent by the host after every move either side makes, with the whole board in base 3
+//!   (see `Ring::to_base3`), e.g. `state 1 02000000`.
+//! - `error <message>`: sent by the host instead of `state` when the client tries to make a move it isn't allowed to.
+//!
+//! The host is the only one who actually checks moves; the client just believes whatever state it's sent.
+
+use std::fmt;
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::io;
+use std::io::BufRead;
+use std::io::BufReader;
+use std::io::Write;
+use std::net::TcpListener;
+use std::net::TcpStream;
+use std::net::ToSocketAddrs;
+
+use crate::Board;
+use crate::BoardError;
+use crate::Glyph;
+use crate::MoveError;
+use crate::Player;
+use crate::Position;
+use crate::Ring;
+
+/// One line of the protocol.
+#[derive(Debug, Clone)]
+pub enum Message {
+    Game { cells: u8 },
+    Move(Position),
+    State { center: Glyph, ring: Ring },
+    Error(String),
+}
+
+impl Message {
+    /// Parse a line of the protocol, without the newline on the end.
+    pub fn parse(line: &str) -> Option<Self> {
+        let (kind, rest) = line.split_once(' ')?;
+        match kind {
+            "game" => rest.parse().ok().map(|cells| Self::Game { cells }),
+            "move" if rest == "c" => Some(Self::Move(Position::Center)),
+            "move" => rest.parse().ok().map(Position::Ring).map(Self::Move),
+            "state" => {
+                let (center, ring) = rest.split_once(' ')?;
+                let center = match center {
+                    "0" => Glyph::None,
+                    "1" => Glyph::X,
+                    "2" => Glyph::O,
+                    _ => return None,
+                };
+                let ring = Ring::from_base3(ring).ok()?;
+                Some(Self::State { center, ring })
+            }
+            "error" => Some(Self::Error(rest.to_owned())),
+            _ => None,
+        }
+    }
+}
+
+impl Display for Message {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Game { cells } => write!(f, "game {}", cells),
+            Self::Move(Position::Center) => write!(f, "move c"),
+            Self::Move(Position::Ring(i)) => write!(f, "move {}", i),
+            Self::State { center, ring } => {
+                let center = match center {
+                    Glyph::None => 0,
+                    Glyph::X => 1,
+                    Glyph::O => 2,
+                };
+                write!(f, "state {} {}", center, ring.to_base3())
+            }
+            // Newlines would split the message in two, so get rid of them.
+            Self::Error(message) => write!(f, "error {}", message.replace('\n', " ")),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum NetError {
+    Io(io::Error),
+    /// The other side hung up.
+    Closed,
+    /// The other side sent something which didn't make sense at that point.
+    Protocol(String),
+    /// It's the other player's turn.
+    NotYourTurn,
+    /// The game can't be played on a ring of that many cells.
+    Board(BoardError),
+    /// The host's own move wasn't allowed.
+    Move(MoveError),
+    /// The host didn't allow the client's move, and sent back this reason why.
+    Rejected(String),
+}
+
+impl From<io::Error> for NetError {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+impl Display for NetError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "{}", err),
+            Self::Closed => write!(f, "the connection was closed"),
+            Self::Protocol(line) => write!(f, "unexpected message '{}'", line),
+            Self::NotYourTurn => write!(f, "it isn't your turn"),
+            Self::Board(err) => write!(f, "{}", err),
+            Self::Move(err) => write!(f, "{}", err),
+            Self::Rejected(message) => write!(f, "move rejected: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for NetError {}
+
+/// The sending and receiving of lines that both ends of the connection need.
+struct Connection {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+}
+
+impl Connection {
+    fn new(stream: TcpStream) -> io::Result<Self> {
+        Ok(Self {
+            reader: BufReader::new(stream.try_clone()?),
+            writer: stream,
+        })
+    }
+
+    fn send(&mut self, message: &Message) -> io::Result<()> {
+        writeln!(self.writer, "{}", message)
+    }
+
+    fn receive(&mut self) -> Result<Message, NetError> {
+        let mut line = String::new();
+        if self.reader.read_line(&mut line)? == 0 {
+            return Err(NetError::Closed);
+        }
+
+        let line = line.trim_end();
+        Message::parse(line).ok_or_else(|| NetError::Protocol(line.to_owned()))
+    }
+}
+
+/// The side of a network game which keeps track of the board and plays as X.
+pub struct Host {
+    board: Board,
+    connection: Connection,
+}
+
+impl Host {
+    /// Wait for somebody to connect to `listener`, and start a game with them on a ring of `cells` cells.
+    pub fn accept(listener: &TcpListener, cells: u8) -> Result<Self, NetError> {
+        // Check this before anybody connects, so that they don't get told about a game that can't happen.
+        let board = Board::try_new(cells).map_err(NetError::Board)?;
+
+        let (stream, _) = listener.accept()?;
+        let mut connection = Connection::new(stream)?;
+        connection.send(&Message::Game { cells })?;
+
+        Ok(Self { board, connection })
+    }
+
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    /// Make a move as X, and tell the client about it.
+    pub fn play(&mut self, mv: Position) -> Result<(), NetError> {
+        if self.board.turn() != Player::X {
+            return Err(NetError::NotYourTurn);
+        }
+
+        self.board.play(mv).map_err(NetError::Move)?;
+        self.send_state()
+    }
+
+    /// Wait for the client to make a move, and return it.
+    ///
+    /// If the client tries to make a move it isn't allowed to, it gets told why and this keeps waiting.
+    pub fn wait(&mut self) -> Result<Position, NetError> {
+        loop {
+            let mv = match self.connection.receive()? {
+                Message::Move(mv) => mv,
+                message => return Err(NetError::Protocol(message.to_string())),
+            };
+
+            let result = if self.board.turn() == Player::O {
+                self.board.play(mv).map_err(|err| err.to_string())
+            } else {
+                Err(NetError::NotYourTurn.to_string())
+            };
+
+            match result {
+                Ok(()) => {
+                    self.send_state()?;
+                    return Ok(mv);
+                }
+                Err(message) => self.connection.send(&Message::Error(message))?,
+            }
+        }
+    }
+
+    fn send_state(&mut self) -> Result<(), NetError> {
+        self.connection.send(&Message::State {
+            center: self.board.center,
+            ring: self.board.ring,
+        })?;
+        Ok(())
+    }
+}
+
+/// The side of a network game which connects to a `Host` and plays as O.
+pub struct Client {
+    board: Board,
+    connection: Connection,
+}
+
+impl Client {
+    pub fn connect(addr: impl ToSocketAddrs) -> Result<Self, NetError> {
+        let mut connection = Connection::new(TcpStream::connect(addr)?)?;
+        let board = match connection.receive()? {
+            Message::Game { cells } => Board::try_new(cells).map_err(NetError::Board)?,
+            message => return Err(NetError::Protocol(message.to_string())),
+        };
+
+        Ok(Self { board, connection })
+    }
+
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    /// Make a move as O, and wait for the host to say whether it's allowed.
+    pub fn play(&mut self, mv: Position) -> Result<(), NetError> {
+        if self.board.turn() != Player::O {
+            return Err(NetError::NotYourTurn);
+        }
+
+        self.connection.send(&Message::Move(mv))?;
+        self.receive_state()
+    }
+
+    /// Wait for the host to make a move.
+    pub fn wait(&mut self) -> Result<(), NetError> {
+        self.receive_state()
+    }
+
+    fn receive_state(&mut self) -> Result<(), NetError> {
+        match self.connection.receive()? {
+            Message::State { center, ring } if ring.len() == self.board.ring.len() => {
+                self.board = Board { center, ring };
+                Ok(())
+            }
+            Message::Error(message) => Err(NetError::Rejected(message)),
+            message => Err(NetError::Protocol(message.to_string())),
+        }
+    }
+}
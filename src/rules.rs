@@ -0,0 +1,229 @@
+//! The settings a `Game` is played with, all in one place.
+//!
+//! A `Board` on its own always follows the normal rules: three in a row wins, and the center can be played in. A
+//! `RuleSet` says how a particular game differs from that, and `Game` follows it when it works out which moves are
+//! allowed and who's won.
+
+use alloc::vec::Vec;
+use core::fmt;
+use core::fmt::Display;
+use core::fmt::Formatter;
+#[cfg(feature = "std")]
+use std::error::Error;
+
+use crate::Board;
+use crate::BoardError;
+use crate::GameStatus;
+use crate::Move;
+use crate::Player;
+use crate::Position;
+use crate::Win;
+
+/// Which game is being played, on top of the settings in `RuleSet`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Variant {
+    /// Take turns putting glyphs in empty cells until somebody wins or the board fills up.
+    #[default]
+    Standard,
+    /// Like `Standard`, but instead of putting a glyph down, a player can spend their turn turning the ring one cell
+    /// (see `Game::rotate`).
+    ///
+    /// Every line goes around with the ring, so this doesn't change who's got what lined up; it's a way of passing,
+    /// for when any move you could make would only help your opponent. To stop the game going on forever, a rotation
+    /// can't put the board back exactly how it was earlier with the same player to move.
+    Rotation,
+    /// Order & Chaos: X plays as Order, who wins by completing a line of either glyph, and O plays as Chaos, who wins
+    /// if the board fills up without one. Either player can put down either glyph (see `Game::place`), so the line
+    /// doesn't have to be made of X's glyphs for Order to win, and there are no draws.
+    OrderAndChaos,
+    /// Each player can only have `limit` glyphs on the board at once. Once they've got that many, putting down another
+    /// takes their oldest one away first, so the board never fills up and there are no draws. That means there have to
+    /// be more cells than both players' glyphs put together.
+    Fading { limit: u8 },
+    /// Ring morris: once a player has put down `pieces` glyphs, they can't put down any more, and instead have to spend
+    /// their turns sliding one of them into an empty cell next to it (see `Position::is_adjacent`). That includes the
+    /// center, unless it's turned off.
+    ///
+    /// A player who can't slide anything loses, and if the same position comes up three times with the same player to
+    /// move, it's a draw.
+    Morris { pieces: u8 },
+}
+
+/// Every setting that changes how a game is played. `RuleSet::default()` is the normal game on a normal-sized board.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RuleSet {
+    /// How many cells there are around the ring.
+    pub cells: u8,
+    /// How many glyphs in a row around the ring it takes to win.
+    ///
+    /// Lines through the center are only ever 3 cells long, so they only count when this is 3.
+    pub win_length: u8,
+    /// Whether the center can be played in. If it can't, it stays empty and the only way to win is around the ring.
+    pub center_enabled: bool,
+    /// Whether completing a line loses instead of winning.
+    pub misere: bool,
+    pub variant: Variant,
+}
+
+impl RuleSet {
+    /// The normal rules, on a ring with `cells` cells.
+    pub fn new(cells: u8) -> Self {
+        Self {
+            cells,
+            win_length: 3,
+            center_enabled: true,
+            misere: false,
+            variant: Variant::Standard,
+        }
+    }
+
+    /// Check that a game can actually be played with these rules.
+    pub fn validate(&self) -> Result<(), RuleError> {
+        Board::try_new(self.cells).map_err(RuleError::Board)?;
+        // Rings smaller than 3 cells are allowed normally, they just don't have any lines around the ring, so only stop
+        // lines being longer than the ring when they're longer than usual.
+        if self.win_length < 3 || self.win_length > self.cells.max(3) {
+            return Err(RuleError::WinLength {
+                length: self.win_length,
+                cells: self.cells,
+            });
+        }
+        if let Variant::Fading { limit } = self.variant {
+            if limit < self.win_length {
+                return Err(RuleError::FadingLimit {
+                    limit,
+                    win_length: self.win_length,
+                });
+            }
+            // The center only counts if it can be played in, same as with morris.
+            let cells = u16::from(self.cells) + u16::from(self.center_enabled);
+            if u16::from(limit) * 2 >= cells {
+                return Err(RuleError::FadingFills {
+                    limit,
+                    cells: self.cells,
+                });
+            }
+        }
+        if let Variant::Morris { pieces } = self.variant {
+            // There has to be somewhere left to slide into once everyone's put all their pieces down, and the center
+            // only counts if it can be played in.
+            let cells = u16::from(self.cells) + u16::from(self.center_enabled);
+            if pieces < self.win_length || u16::from(pieces) * 2 >= cells {
+                return Err(RuleError::Pieces {
+                    pieces,
+                    cells: self.cells,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether these rules allow this kind of move at all, ignoring what's on the board.
+    pub fn allows(&self, mv: Move) -> bool {
+        match mv {
+            Move::Place { pos, player, glyph } => {
+                (pos != Position::Center || self.center_enabled)
+                    && glyph.is_occupied()
+                    && (glyph == player || self.variant == Variant::OrderAndChaos)
+            }
+            Move::Rotate { .. } => self.variant == Variant::Rotation,
+            Move::Slide { from, to, .. } => {
+                matches!(self.variant, Variant::Morris { .. })
+                    && (self.center_enabled || (from != Position::Center && to != Position::Center))
+            }
+        }
+    }
+
+    /// Get all the lines on `board` which are long enough to count as wins under these rules.
+    ///
+    /// When `win_length` is more than 3 the ring wins are merged like `Board::wins_merged`, since they need the length
+    /// of the whole run.
+    pub fn wins(&self, board: &Board) -> Vec<Win> {
+        if self.win_length == 3 {
+            return board.wins();
+        }
+        board
+            .wins_merged()
+            .into_iter()
+            .filter(|win| match *win {
+                Win::Ring { length, .. } => length >= self.win_length,
+                Win::Center { .. } => false,
+            })
+            .collect()
+    }
+
+    /// Whether the game on `board` is still going under these rules, and if not, how it ended.
+    pub fn status(&self, board: &Board) -> GameStatus {
+        let full = board.ring.is_full() && (board.center.is_occupied() || !self.center_enabled);
+        let order_and_chaos = self.variant == Variant::OrderAndChaos;
+        let status = match self.wins(board).first() {
+            // In Order & Chaos it doesn't matter whose glyphs the line is made of, it's always Order who wins.
+            Some(_) if order_and_chaos => GameStatus::Won(Player::X),
+            None if full && order_and_chaos => GameStatus::Won(Player::O),
+            Some(win) => GameStatus::Won(win.winner()),
+            None if full => GameStatus::Draw,
+            None => GameStatus::InProgress,
+        };
+        match status {
+            // In misère, completing a line loses instead, which means the other player wins.
+            GameStatus::Won(player) if self.misere => GameStatus::Won(player.opponent()),
+            status => status,
+        }
+    }
+}
+
+impl Default for RuleSet {
+    fn default() -> Self {
+        Self::new(Board::DEFAULT_CELLS)
+    }
+}
+
+/// The reasons why a `RuleSet` might not be playable.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RuleError {
+    /// A board can't have that many cells.
+    Board(BoardError),
+    /// The win length is less than 3, or longer than the ring.
+    WinLength { length: u8, cells: u8 },
+    /// With `Variant::Fading`, the players can't have enough glyphs on the board at once to make a line.
+    FadingLimit { limit: u8, win_length: u8 },
+    /// With `Variant::Fading`, the players can have so many glyphs on the board at once that they could fill it up,
+    /// which would make the game a draw.
+    FadingFills { limit: u8, cells: u8 },
+    /// With `Variant::Morris`, the players don't have enough pieces to make a line, or they have so many that there's
+    /// nowhere to slide them.
+    Pieces { pieces: u8, cells: u8 },
+}
+
+impl Display for RuleError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Board(err) => write!(f, "{}", err),
+            Self::WinLength { length, cells } => write!(
+                f,
+                "can't need {} in a row to win on a {}-cell ring",
+                length, cells
+            ),
+            Self::FadingLimit { limit, win_length } => write!(
+                f,
+                "can't get {} in a row with only {} glyphs each",
+                win_length, limit
+            ),
+            Self::FadingFills { limit, cells } => write!(
+                f,
+                "{} glyphs each could fill up a {}-cell ring",
+                limit, cells
+            ),
+            Self::Pieces { pieces, cells } => write!(
+                f,
+                "can't play morris with {} pieces each on a {}-cell ring",
+                pieces, cells
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl Error for RuleError {}
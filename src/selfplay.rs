@@ -0,0 +1,110 @@
+//! Pitting strategies against each other without any rendering, to see which one plays better.
+
+use crate::Board;
+use crate::GameResult;
+use crate::Glyph;
+use crate::Player;
+use crate::Position;
+
+/// The totals from a batch of games played by `self_play`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Stats {
+    pub x_wins: u32,
+    pub o_wins: u32,
+    pub draws: u32,
+    /// The total number of moves made across all the games.
+    pub moves: u32,
+}
+
+impl Stats {
+    pub fn games(&self) -> u32 {
+        self.x_wins + self.o_wins + self.draws
+    }
+
+    /// The average number of moves it took for a game to finish.
+    pub fn average_length(&self) -> f32 {
+        self.moves as f32 / self.games() as f32
+    }
+}
+
+/// Play `games` games on a ring of `cells` cells, with `x` picking the moves for X and `o` picking the moves for O.
+///
+/// The strategies are `FnMut` so that they can keep state between moves, e.g. a random number generator;
+/// if they're both deterministic, every game will play out exactly the same.
+///
+/// Panics if a strategy picks a move which isn't allowed.
+pub fn self_play(
+    cells: u8,
+    games: u32,
+    mut x: impl FnMut(&Board) -> Position,
+    mut o: impl FnMut(&Board) -> Position,
+) -> Stats {
+    let mut stats = Stats::default();
+
+    for _ in 0..games {
+        let mut board = Board::new(cells);
+
+        let result = loop {
+            match board.result() {
+                GameResult::InProgress => {}
+                result => break result,
+            }
+
+            let mv = match board.turn() {
+                Player::X => x(&board),
+                Player::O => o(&board),
+            };
+
+            board.play(mv).expect("strategy picked an illegal move");
+            stats.moves += 1;
+        };
+
+        match result {
+            GameResult::Won(Player::X) => stats.x_wins += 1,
+            GameResult::Won(Player::O) => stats.o_wins += 1,
+            GameResult::Draw => stats.draws += 1,
+            GameResult::InProgress => unreachable!(),
+        }
+    }
+
+    stats
+}
+
+/// A strategy which plays perfectly, using `Board::best_move`.
+pub fn perfect(board: &Board) -> Position {
+    board.best_move().expect("game is already over")
+}
+
+/// A strategy which picks whichever move `Board::evaluate` likes best, without looking any further ahead.
+pub fn greedy(board: &Board) -> Position {
+    let mut board = *board;
+
+    let mut best = None;
+    let mut best_score = i8::MIN;
+    for mv in board.available_moves() {
+        board.play(mv).unwrap();
+        // `evaluate` scores it for the opponent, since it's their turn now.
+        let score = -board.evaluate();
+        board.set(mv, Glyph::None);
+
+        if score > best_score {
+            best = Some(mv);
+            best_score = score;
+        }
+    }
+
+    best.expect("game is already over")
+}
+
+/// A strategy which wins straight away if it can, and otherwise blocks the opponent from winning next turn,
+/// using `Board::threats`. If neither's possible it just picks the first move available.
+pub fn blocking(board: &Board) -> Position {
+    let turn = board.turn();
+    board
+        .threats(turn)
+        .into_iter()
+        .chain(board.threats(turn.opponent()))
+        .chain(board.available_moves())
+        .next()
+        .expect("game is already over")
+}
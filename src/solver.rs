@@ -0,0 +1,182 @@
+//! The minimax search behind `Board::best_move`, with a cache of positions it's already solved.
+
+use std::collections::HashMap;
+
+use crate::Board;
+use crate::GameResult;
+use crate::Glyph;
+use crate::Position;
+
+/// The score the search gives to a win, on top of the number of empty cells left afterwards.
+/// This has to be more than `Board::evaluate` can ever return.
+pub(crate) const WIN_SCORE: i16 = 1000;
+
+/// What a score in the cache actually means, since alpha-beta pruning means that scores aren't always exact.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Bound {
+    /// The score is exactly right.
+    Exact,
+    /// The search stopped early because the score was too high to matter; the real score is at least this.
+    Lower,
+    /// None of the moves were good enough to matter; the real score is at most this.
+    Upper,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Entry {
+    score: i16,
+    /// How many moves ahead the search looked to get `score`.
+    depth: u8,
+    bound: Bound,
+}
+
+/// A minimax searcher which remembers the scores of the positions it's seen, so that it doesn't have to work them out
+/// again when it comes across them later, either in the same search or in a later one.
+///
+/// Positions are looked up by `Board`'s symmetry-aware `Hash` and `PartialEq`, so a position's score also gets reused
+/// for all of its rotations and reflections.
+///
+/// By default it plays the normal game; use `Solver::misere` for misère, where completing a line loses.
+#[derive(Default)]
+pub struct Solver {
+    cache: HashMap<Board, Entry>,
+    nodes: u64,
+    misere: bool,
+}
+
+impl Solver {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a solver for misère play (see `RuleSet::misere`), which tries to avoid completing a line rather than
+    /// trying to complete one.
+    ///
+    /// The optimal moves are completely different, so this keeps its own cache rather than sharing one with a normal
+    /// solver.
+    pub fn misere() -> Self {
+        Self {
+            misere: true,
+            ..Self::default()
+        }
+    }
+
+    /// The same as `Board::best_move`, but using and adding to this solver's cache.
+    pub fn best_move(&mut self, board: &Board) -> Option<Position> {
+        // There are never more than `Ring::MAX_CELLS + 1` cells, so the search can't get any deeper than this.
+        self.best_move_depth(board, u8::MAX)
+    }
+
+    /// The same as `Board::best_move_depth`, but using and adding to this solver's cache.
+    pub fn best_move_depth(&mut self, board: &Board, max_depth: u8) -> Option<Position> {
+        let turn = board.turn();
+        let mut board = *board;
+
+        let mut best = None;
+        // Use `-i16::MAX` rather than `i16::MIN` as negative infinity, so that it can be negated without overflowing.
+        let mut alpha = -i16::MAX;
+        for mv in board.legal_moves_canonical() {
+            board.set(mv, turn.glyph());
+            let score = -self.negamax(&mut board, max_depth.saturating_sub(1), -i16::MAX, -alpha);
+            board.set(mv, Glyph::None);
+
+            if score > alpha {
+                best = Some(mv);
+                alpha = score;
+            }
+        }
+
+        best
+    }
+
+    /// The number of positions in the cache.
+    pub fn cache_len(&self) -> usize {
+        self.cache.len()
+    }
+
+    /// Forget every position in the cache.
+    pub fn clear(&mut self) {
+        self.cache.clear();
+    }
+
+    /// The total number of positions this solver has searched, including ones it found in the cache.
+    pub fn nodes(&self) -> u64 {
+        self.nodes
+    }
+
+    /// Get the score of the board for whoever's turn it is, assuming perfect play from both sides,
+    /// and looking at most `depth` moves ahead before falling back to `evaluate`.
+    ///
+    /// Wins and losses are worth more than anything `evaluate` can return, with ones which happen sooner further from 0
+    /// so that the AI doesn't dawdle. A draw is 0.
+    ///
+    /// This uses alpha-beta pruning: as soon as it's clear that the score is going to be `beta` or more,
+    /// the opponent would never let the game get here, so it stops looking and just returns what it's found so far.
+    /// Scores of `alpha` or less are similarly not worth being precise about.
+    fn negamax(&mut self, board: &mut Board, depth: u8, mut alpha: i16, mut beta: i16) -> i16 {
+        self.nodes += 1;
+
+        match board.result() {
+            // The only person who could've completed a line is the player who just moved, so this is a loss, unless
+            // it's misère, in which case they've just lost instead.
+            GameResult::Won(_) if self.misere => return WIN_SCORE + board.empty_cells() as i16,
+            GameResult::Won(_) => return -(WIN_SCORE + board.empty_cells() as i16),
+            GameResult::Draw => return 0,
+            GameResult::InProgress => {}
+        }
+
+        if depth == 0 {
+            // `evaluate` counts lines that are close to being completed as good, which is backwards for misère.
+            let score = i16::from(board.evaluate());
+            return if self.misere { -score } else { score };
+        }
+
+        if let Some(entry) = self.cache.get(board) {
+            // A shallower search's score isn't good enough, since it might have missed something.
+            if entry.depth >= depth {
+                match entry.bound {
+                    Bound::Exact => return entry.score,
+                    Bound::Lower => alpha = alpha.max(entry.score),
+                    Bound::Upper => beta = beta.min(entry.score),
+                }
+                if alpha >= beta {
+                    return entry.score;
+                }
+            }
+        }
+
+        let original_alpha = alpha;
+        let turn = board.turn();
+
+        let mut best = -i16::MAX;
+        for mv in board.legal_moves_canonical() {
+            board.set(mv, turn.glyph());
+            let score = -self.negamax(board, depth - 1, -beta, -alpha);
+            board.set(mv, Glyph::None);
+
+            best = best.max(score);
+            alpha = alpha.max(score);
+            if alpha >= beta {
+                break;
+            }
+        }
+
+        let bound = if best <= original_alpha {
+            Bound::Upper
+        } else if best >= beta {
+            Bound::Lower
+        } else {
+            Bound::Exact
+        };
+        self.cache.insert(
+            *board,
+            Entry {
+                score: best,
+                depth,
+                bound,
+            },
+        );
+
+        best
+    }
+}
@@ -0,0 +1,52 @@
+//! `proptest` strategies for generating rings and boards, so that property tests don't all need their own generators.
+
+use ::proptest::collection::vec;
+use ::proptest::prelude::*;
+
+use crate::Board;
+use crate::GameResult;
+use crate::Glyph;
+use crate::Ring;
+
+/// Generate any glyph, including `Glyph::None`.
+pub fn any_glyph() -> impl Strategy<Value = Glyph> {
+    prop_oneof![Just(Glyph::None), Just(Glyph::X), Just(Glyph::O)]
+}
+
+/// Generate any ring with `cells` cells.
+///
+/// Panics if `cells` is 0 or more than `Ring::MAX_CELLS`.
+pub fn any_ring(cells: u8) -> impl Strategy<Value = Ring> {
+    // Check this now, rather than the first time a value gets generated.
+    Ring::new(cells);
+    (0..3u128.pow(cells.into())).prop_map(move |int| Ring { int, cells })
+}
+
+/// Generate any board with `cells` cells in its ring, including ones that couldn't come up in a real game.
+///
+/// Panics if `cells` is 0 or more than `Ring::MAX_CELLS`.
+pub fn any_board(cells: u8) -> impl Strategy<Value = Board> {
+    (any_glyph(), any_ring(cells)).prop_map(|(center, ring)| Board { center, ring })
+}
+
+/// Generate a board with `cells` cells in its ring that can come up in an actual game, by playing random moves from an
+/// empty board. The game might have finished, or it might still be going.
+///
+/// Panics if `cells` is 0 or more than `Ring::MAX_CELLS`.
+pub fn reachable_board(cells: u8) -> impl Strategy<Value = Board> {
+    Ring::new(cells);
+    // Each of these picks which of the legal moves gets made next. Shrinking them makes the game shorter, and makes
+    // the moves earlier ones in `legal_moves`.
+    vec(any::<u8>(), 0..=usize::from(cells) + 1).prop_map(move |choices| {
+        let mut board = Board::new(cells);
+        for choice in choices {
+            if board.result() != GameResult::InProgress {
+                break;
+            }
+            let moves: Vec<_> = board.legal_moves(board.turn()).collect();
+            let mv = moves[usize::from(choice) % moves.len()];
+            board.play(mv).unwrap();
+        }
+        board
+    })
+}
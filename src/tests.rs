@@ -1,13 +1,69 @@
+use std::cell::RefCell;
+use std::cmp::Ordering;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashSet;
+use std::convert::TryFrom;
 use std::convert::TryInto;
+use std::hash::Hash;
+use std::hash::Hasher;
+use std::rc::Rc;
+use std::time::Duration;
 
+use crate::clock::Clock;
+use crate::encode_code;
+use crate::enumerate;
+use crate::game::Game;
+use crate::game::GameEvent;
+use crate::geometry;
+use crate::geometry::Layout;
+use crate::multiplayer::MultiBoard;
+use crate::multiplayer::MultiGame;
+use crate::multiplayer::MultiStatus;
+use crate::multiplayer::Seat;
+use crate::multiplayer::SetupError;
+use crate::multiring::MultiRing;
+use crate::multiring::Place;
+use crate::rules::RuleError;
+use crate::rules::RuleSet;
+use crate::rules::Variant;
+use crate::selfplay;
+use crate::solver::Solver;
+use crate::solver::WIN_SCORE;
+use crate::ultimate::UltimateGame;
+use crate::ultimate::UltimateMove;
+use crate::zobrist;
 use crate::Board;
+use crate::BoardBuilder;
+use crate::BoardError;
+use crate::CanonicalRing;
+use crate::DecodeError;
+use crate::DiffError;
+use crate::Dihedral;
+use crate::Direction;
+use crate::GameResult;
+use crate::GameStatus;
 use crate::Glyph;
+use crate::Line;
+use crate::Literal;
+use crate::Move;
+use crate::MoveError;
+use crate::ParseRingError;
+use crate::Player;
+use crate::Position;
 use crate::Ring;
+use crate::Win;
+use crate::CODE_ALPHABET;
+
+fn hash(value: impl Hash) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
 
 fn ring(str: &str) -> Ring {
     Ring {
         cells: str.len().try_into().expect("too many cells"),
-        int: u32::from_str_radix(str, 3).unwrap(),
+        int: u128::from_str_radix(str, 3).unwrap(),
     }
 }
 
@@ -39,12 +95,24 @@ fn shifting() {
     assert_eq!(ring("01201201") << 8, ring("01201201"));
 
     assert_eq!((ring("00000002") << 1).int, ring("00000020").int);
+
+    let mut shifted = ring("01201201");
+    shifted >>= 3;
+    assert_eq!(shifted.int, ring("20101201").int);
+    shifted <<= 11;
+    assert_eq!(shifted.int, ring("01201201").int);
+    assert_eq!(shifted.rotate_left(9).int, ring("12012010").int);
+    assert_eq!(shifted.rotate_right(1).int, ring("10120120").int);
 }
 
 #[test]
 fn printing() {
     assert_eq!(ring("01201201").to_string(), " XO XO X".to_string());
     assert_eq!(ring("22222222").to_string(), "OOOOOOOO".to_string());
+    assert_eq!(
+        ring("01201201").fmt_with('.', 'x', 'o'),
+        ".xo.xo.x".to_string()
+    );
 }
 
 #[test]
@@ -55,7 +123,7 @@ fn winner() {
             ring: ring("00111020")
         }
         .winner(),
-        Glyph::X
+        Some(Player::X)
     );
     assert_eq!(
         Board {
@@ -63,7 +131,7 @@ fn winner() {
             ring: ring("00222010")
         }
         .winner(),
-        Glyph::O
+        Some(Player::O)
     );
 
     assert_eq!(
@@ -72,7 +140,7 @@ fn winner() {
             ring: ring("10221211")
         }
         .winner(),
-        Glyph::X
+        Some(Player::X)
     );
     assert_eq!(
         Board {
@@ -80,7 +148,7 @@ fn winner() {
             ring: ring("22012102")
         }
         .winner(),
-        Glyph::O
+        Some(Player::O)
     );
 
     assert_eq!(
@@ -89,7 +157,7 @@ fn winner() {
             ring: ring("11201202")
         }
         .winner(),
-        Glyph::X
+        Some(Player::X)
     );
     assert_eq!(
         Board {
@@ -97,13 +165,2778 @@ fn winner() {
             ring: ring("21012102")
         }
         .winner(),
-        Glyph::O
+        Some(Player::O)
     );
 }
 
 #[test]
 fn reverse() {
-    assert_eq!(ring("00000002").reverse().int, ring("20000000").int);
-    assert_eq!(ring("012012012").reverse().int, ring("210210210").int);
-    assert_eq!(ring("22222222").reverse().int, ring("22222222").int);
+    assert_eq!(ring("00000002").reversed().int, ring("20000000").int);
+    assert_eq!(ring("012012012").reversed().int, ring("210210210").int);
+    assert_eq!(ring("22222222").reversed().int, ring("22222222").int);
+
+    for digits in ["0", "01", "0120", "01201201", "12000000000000000002"] {
+        let ring = ring(digits);
+        assert_eq!(ring.reversed().reversed().int, ring.int);
+        assert_eq!(ring.reversed().reversed().cells, ring.cells);
+    }
+}
+
+#[test]
+fn reflect() {
+    assert_eq!(ring("01201201").reflect(0).int, ring("01021021").int);
+    assert_eq!(ring("01201201").reflect(1).int, ring("10102102").int);
+    assert_eq!(ring("01201201").reflect(9).int, ring("10102102").int);
+    assert_eq!(
+        ring("01201201").reflect(7).int,
+        ring("01201201").reversed().int
+    );
+
+    for axis in 0..10 {
+        let ring = ring("00112");
+        assert_eq!(ring.reflect(axis).reflect(axis).int, ring.int);
+        assert_eq!(ring.reflect(axis).int, ring.reflect(axis + 5).int);
+        assert_eq!(
+            ring.reflect(axis).int,
+            ring.transform(Dihedral::Reflect(axis)).int
+        );
+    }
+}
+
+#[test]
+fn turn() {
+    let mut board = Board::new(8);
+    assert_eq!(board.turn(), Glyph::X);
+    board.play(Position::Ring(0)).unwrap();
+    assert_eq!(board.turn(), Glyph::O);
+    board.play(Position::Center).unwrap();
+    assert_eq!(board.turn(), Glyph::X);
+}
+
+#[test]
+fn play() {
+    let mut board = Board::new(8);
+    board.play(Position::Ring(3)).unwrap();
+    assert_eq!(board.ring.get(3), Glyph::X);
+    assert_eq!(board.play(Position::Ring(3)), Err(MoveError::Occupied));
+    assert_eq!(
+        board.play(Position::Ring(8)),
+        Err(MoveError::OutOfRange { index: 8, cells: 8 })
+    );
+
+    board.play(Position::Center).unwrap();
+    assert_eq!(board.center, Glyph::O);
+    assert_eq!(board.play(Position::Center), Err(MoveError::Occupied));
+
+    let mut board = Board {
+        center: Glyph::None,
+        ring: ring("11102200"),
+    };
+    assert_eq!(board.play(Position::Center), Err(MoveError::GameOver));
+}
+
+#[test]
+fn apply_undo() {
+    let mut board = Board::new(8);
+    let mv = Move::Place {
+        pos: Position::Ring(2),
+        player: Player::X,
+        glyph: Glyph::X,
+    };
+    board.apply(mv).unwrap();
+    assert_eq!(board.ring.get(2), Glyph::X);
+    assert_eq!(board.apply(mv), Err(MoveError::WrongTurn));
+    assert_eq!(
+        board.apply(Move::Place {
+            pos: Position::Ring(2),
+            player: Player::O,
+            glyph: Glyph::O,
+        }),
+        Err(MoveError::Occupied)
+    );
+
+    board.undo(mv);
+    assert!(board.ring.is_empty());
+    assert_eq!(board.turn(), Glyph::X);
+}
+
+#[test]
+#[should_panic]
+fn undo_wrong_player() {
+    let mut board = Board::new(8);
+    board.play(Position::Center).unwrap();
+    board.undo(Move::Place {
+        pos: Position::Center,
+        player: Player::O,
+        glyph: Glyph::O,
+    });
+}
+
+#[test]
+fn zobrist() {
+    let mut board = Board::new(8);
+    let mut hash = board.zobrist();
+    assert_ne!(hash, Board::new(9).zobrist());
+
+    let moves = [
+        Move::Place {
+            pos: Position::Ring(3),
+            player: Player::X,
+            glyph: Glyph::X,
+        },
+        Move::Place {
+            pos: Position::Center,
+            player: Player::O,
+            glyph: Glyph::O,
+        },
+    ];
+    for mv in moves {
+        board.apply_zobrist(mv, &mut hash).unwrap();
+        assert_eq!(hash, board.zobrist());
+    }
+
+    // An illegal move shouldn't change anything.
+    assert_eq!(
+        board.apply_zobrist(moves[0], &mut hash),
+        Err(MoveError::Occupied)
+    );
+    assert_eq!(hash, board.zobrist());
+
+    let rotated = board.transform(Dihedral::Rotate(2));
+    assert_ne!(rotated.zobrist(), board.zobrist());
+    assert_eq!(rotated.zobrist_canonical(), board.zobrist_canonical());
+
+    board.undo_zobrist(moves[1], &mut hash);
+    board.undo_zobrist(moves[0], &mut hash);
+    assert_eq!(hash, Board::new(8).zobrist());
+}
+
+#[test]
+fn legal_moves() {
+    let mut board = Board::new(6);
+    assert_eq!(board.legal_moves(Player::X).count(), 7);
+    assert_eq!(board.legal_moves(Player::O).count(), 0);
+
+    board.play(Position::Center).unwrap();
+    board.play(Position::Ring(4)).unwrap();
+    assert_eq!(
+        board.legal_moves(Player::X).collect::<Vec<_>>(),
+        [0, 1, 2, 3, 5].map(Position::Ring)
+    );
+
+    let board = Board {
+        center: Glyph::None,
+        ring: ring("111220"),
+    };
+    assert_eq!(board.legal_moves(Player::O).count(), 0);
+    assert_eq!(board.legal_moves(Player::X).count(), 0);
+}
+
+#[test]
+fn legal_moves_canonical() {
+    let mut board = Board::new(8);
+    assert_eq!(
+        board.legal_moves_canonical().collect::<Vec<_>>(),
+        [Position::Center, Position::Ring(0)]
+    );
+
+    // With an X at 0, the ring is only symmetric under reflecting it across 0, which pairs up 1 with 7, 2 with 6
+    // and 3 with 5.
+    board.play(Position::Ring(0)).unwrap();
+    assert_eq!(
+        board.legal_moves_canonical().collect::<Vec<_>>(),
+        [
+            Position::Center,
+            Position::Ring(1),
+            Position::Ring(2),
+            Position::Ring(3),
+            Position::Ring(4),
+        ]
+    );
+
+    board.play(Position::Ring(1)).unwrap();
+    assert_eq!(board.legal_moves_canonical().count(), 7);
+}
+
+#[test]
+fn validity() {
+    let board = Board::new(8);
+    assert!(board.is_valid() && board.is_reachable());
+    assert_eq!(board.turn_to_move(), Some(Player::X));
+
+    // Too many Os.
+    let board = Board {
+        center: Glyph::O,
+        ring: ring("20000000"),
+    };
+    assert!(!board.is_valid() && !board.is_reachable());
+    assert_eq!(board.turn_to_move(), None);
+
+    // Both players have won.
+    let board = Board {
+        center: Glyph::None,
+        ring: ring("11102220"),
+    };
+    assert!(!board.is_valid());
+
+    // X won, but then O kept playing.
+    let board = Board {
+        center: Glyph::None,
+        ring: ring("11122020"),
+    };
+    assert!(board.is_valid() && !board.is_reachable());
+    assert_eq!(board.turn_to_move(), None);
+
+    // X won with two lines at once, by putting the last glyph where they cross.
+    let board = Board {
+        center: Glyph::X,
+        ring: ring("11102202"),
+    };
+    assert!(board.is_reachable());
+    assert_eq!(board.turn_to_move(), None);
+
+    // X has two separate wins, so one of them must have been there before the last move.
+    let board = Board {
+        center: Glyph::None,
+        ring: ring("11101112202022"),
+    };
+    assert!(board.is_valid() && !board.is_reachable());
+}
+
+#[test]
+fn legal() {
+    let mut board = Board::new(8);
+    assert!(board.legal(Position::Center));
+    assert!(board.legal(Position::Ring(7)));
+    assert!(!board.legal(Position::Ring(8)));
+
+    board.play(Position::Ring(3)).unwrap();
+    assert!(!board.legal(Position::Ring(3)));
+    assert!(board.legal(Position::Ring(4)));
+
+    // Once the game's over, nothing's allowed, not even empty cells.
+    let board = Board {
+        center: Glyph::None,
+        ring: ring("11102200"),
+    };
+    assert!(!board.legal(Position::Center));
+    assert!(!board.legal(Position::Ring(7)));
+}
+
+#[test]
+fn winning_move() {
+    let board = Board {
+        center: Glyph::O,
+        ring: ring("11002000"),
+    };
+    let after = board.with_move(Position::Ring(2)).unwrap();
+    assert!(after.is_identical(&Board {
+        center: Glyph::O,
+        ring: ring("11102000"),
+    }));
+    // `with_move` shouldn't touch the original.
+    assert_eq!(board.ring.get(2), Glyph::None);
+
+    assert!(board.is_winning_move(Position::Ring(2)));
+    assert!(!board.is_winning_move(Position::Ring(3)));
+    // Occupied and out of range.
+    assert!(!board.is_winning_move(Position::Ring(0)));
+    assert!(!board.is_winning_move(Position::Ring(8)));
+}
+
+#[test]
+fn result() {
+    assert_eq!(Board::new(8).result(), GameResult::InProgress);
+    assert_eq!(
+        Board {
+            center: Glyph::O,
+            ring: ring("22111021")
+        }
+        .result(),
+        GameResult::Won(Player::X)
+    );
+    assert_eq!(
+        Board {
+            center: Glyph::X,
+            ring: ring("11212212")
+        }
+        .result(),
+        GameResult::Draw
+    );
+}
+
+#[test]
+fn status() {
+    let draw = Board {
+        center: Glyph::X,
+        ring: ring("11212212"),
+    };
+    assert_eq!(draw.winner(), None);
+    assert_eq!(draw.status(), GameStatus::Draw);
+    assert_eq!(Board::new(8).winner(), None);
+    assert_eq!(Board::new(8).status(), GameStatus::InProgress);
+}
+
+#[test]
+fn best_move() {
+    // X can win straight away by finishing the line through the center.
+    let board = Board {
+        center: Glyph::X,
+        ring: ring("10200020"),
+    };
+    assert_eq!(board.best_move(), Some(Position::Ring(4)));
+
+    // O has to block X's line along the ring.
+    let board = Board {
+        center: Glyph::None,
+        ring: ring("11000002"),
+    };
+    assert_eq!(board.turn(), Glyph::O);
+    assert_eq!(board.best_move(), Some(Position::Ring(2)));
+}
+
+#[test]
+fn evaluate() {
+    assert_eq!(Board::new(8).evaluate(), 0);
+
+    // It's O's turn, and X has two in a row.
+    let board = Board {
+        center: Glyph::None,
+        ring: ring("11000000"),
+    };
+    assert!(board.evaluate() < 0);
+
+    let board = Board {
+        center: Glyph::None,
+        ring: ring("11122000"),
+    };
+    assert_eq!(board.evaluate(), -i8::MAX);
+}
+
+#[test]
+fn self_play() {
+    let stats = selfplay::self_play(6, 3, selfplay::perfect, selfplay::greedy);
+    assert_eq!(stats.games(), 3);
+    // Perfect play should never lose.
+    assert_eq!(stats.o_wins, 0);
+    assert!(stats.average_length() >= 5.0);
+
+    // Always picking the first available move is a terrible strategy, so it should lose every game.
+    let stats = selfplay::self_play(6, 2, |board| board.available_moves()[0], selfplay::perfect);
+    assert_eq!(stats.o_wins, 2);
+}
+
+#[test]
+#[allow(deprecated)]
+fn opponent() {
+    assert_eq!(Glyph::X.opponent(), Glyph::O);
+    assert_eq!(Glyph::O.opponent(), Glyph::X);
+    assert_eq!(Glyph::None.opponent(), Glyph::None);
+
+    assert_eq!(Player::X.opponent(), Player::O);
+    assert_eq!(Player::O.opponent(), Player::X);
+}
+
+#[test]
+fn player_conversions() {
+    assert_eq!(Glyph::from(Player::X), Glyph::X);
+    assert_eq!(Glyph::from(Some(Player::O)), Glyph::O);
+    assert_eq!(Glyph::from(None::<Player>), Glyph::None);
+    assert_eq!(Glyph::O.player(), Some(Player::O));
+    assert_eq!(Glyph::None.player(), None);
+    assert!(Glyph::X == Player::X && Player::O == Glyph::O);
+    assert!(Glyph::None != Player::X);
+    assert_eq!(Player::O.to_string(), "O");
+}
+
+#[test]
+fn occupancy() {
+    assert!(Glyph::None.is_empty());
+    assert!(!Glyph::X.is_empty());
+    assert!(Glyph::O.is_occupied());
+    assert!(!Glyph::None.is_occupied());
+}
+
+#[test]
+fn count() {
+    assert_eq!(ring("01201201").count(Glyph::None), 3);
+    assert_eq!(ring("01201201").count(Glyph::X), 3);
+    assert_eq!(ring("01201201").count(Glyph::O), 2);
+    assert_eq!(ring("01201201").occupied(), 5);
+
+    assert!(ring("0000").is_empty());
+    assert!(!ring("0010").is_empty());
+    assert!(ring("1221").is_full());
+    assert!(!ring("1201").is_full());
+
+    let mut board = Board {
+        center: Glyph::None,
+        ring: ring("1221"),
+    };
+    assert!(!board.is_full());
+    board.center = Glyph::X;
+    assert!(board.is_full());
+}
+
+#[test]
+fn builder() {
+    let board = BoardBuilder::new(8)
+        .center(Glyph::X)
+        .set(3, Glyph::O)
+        .set(5, Glyph::X)
+        .build()
+        .unwrap();
+    assert_eq!(board.center, Glyph::X);
+    assert_eq!(board.ring.to_string(), "   O X  ");
+
+    assert_eq!(
+        BoardBuilder::new(8).set(8, Glyph::X).build().err(),
+        Some(BoardError::OutOfRange { index: 8, cells: 8 })
+    );
+    assert_eq!(
+        BoardBuilder::new(0).build().err(),
+        Some(BoardError::NoCells)
+    );
+    assert_eq!(
+        BoardBuilder::new(82).build().err(),
+        Some(BoardError::TooManyCells(82))
+    );
+    assert_eq!(BoardBuilder::new(7).build().unwrap().ring.len(), 7);
+
+    let board = BoardBuilder::default()
+        .cells(10)
+        .center(Glyph::O)
+        .ring_from_str("XO  X  O  ")
+        .set(2, Glyph::X)
+        .build()
+        .unwrap();
+    assert_eq!(board.to_string(), "O|XOX X  O  ");
+    assert_eq!(
+        BoardBuilder::default().ring_from_str("XO").build().err(),
+        Some(BoardError::WrongLength {
+            expected: 8,
+            found: 2
+        })
+    );
+    assert_eq!(
+        BoardBuilder::default().ring_from_str("XO?").build().err(),
+        Some(BoardError::Parse(ParseRingError::InvalidChar('?')))
+    );
+
+    assert!(Board::default().is_identical(&Board::new(8)));
+    assert_eq!(BoardBuilder::default().build().unwrap().ring.len(), 8);
+}
+
+#[test]
+fn try_new() {
+    assert_eq!(Board::try_new(0).err(), Some(BoardError::NoCells));
+    assert_eq!(Board::try_new(82).err(), Some(BoardError::TooManyCells(82)));
+    assert_eq!(Board::try_new(8).unwrap().ring.len(), 8);
+
+    assert_eq!(Ring::try_new(0).err(), Some(BoardError::NoCells));
+    assert_eq!(Ring::try_new(81).err(), Some(BoardError::TooManyCells(81)));
+    assert_eq!(Ring::try_new(7).unwrap().len(), 7);
+}
+
+#[test]
+#[should_panic(expected = "a ring needs at least one cell")]
+fn new_panics() {
+    Board::new(0);
+}
+
+#[test]
+#[should_panic(expected = "a ring needs at least one cell")]
+fn from_empty_array_panics() {
+    let _ = Ring::from([]);
+}
+
+#[test]
+fn tiny_rings() {
+    // Lines around rings this small would have to wrap around onto the same cells, so there aren't any.
+    let mut board = Board::new(1);
+    assert!(board.lines().is_empty());
+    board.play(Position::Ring(0)).unwrap();
+    assert_eq!(board.winner(), None);
+    assert!(board.wins_merged().is_empty());
+
+    let mut board = Board::new(2);
+    assert_eq!(board.lines(), [Line::Center { index: 0 }]);
+    for pos in [Position::Ring(0), Position::Center, Position::Ring(1)] {
+        board.play(pos).unwrap();
+    }
+    assert_eq!(board.to_string(), "O|XX");
+    assert!(board.wins_merged().is_empty());
+    assert_eq!(board.status(), GameStatus::Draw);
+
+    let board: Board = "X|XX".parse().unwrap();
+    assert_eq!(
+        board.wins(),
+        [Win::Center {
+            index: 0,
+            winner: Player::X
+        }]
+    );
+}
+
+#[test]
+fn big_rings() {
+    for cells in [22, 40, Ring::MAX_CELLS] {
+        let mut ring = Ring::new(cells);
+        ring.set(0, Glyph::O);
+        ring.set(1, Glyph::X);
+        ring.set(cells - 1, Glyph::O);
+        assert_eq!(ring.get(0), Glyph::O);
+        assert_eq!(ring.get(1), Glyph::X);
+        assert_eq!(ring.get(cells - 1), Glyph::O);
+        assert_eq!(ring.count(Glyph::None), cells - 3);
+        assert_eq!(ring.iter().len(), usize::from(cells));
+
+        // Shifting all the way around should put everything back where it started,
+        // without anything getting lost off the end along the way.
+        let mut shifted = ring;
+        for _ in 0..cells {
+            shifted <<= 1;
+            assert_eq!(shifted.count(Glyph::O), 2);
+        }
+        assert!(Literal(shifted) == Literal(ring));
+        assert_eq!((ring >> 1).get(2), Glyph::X);
+        assert_eq!((ring << 1).get(0), Glyph::X);
+
+        assert_eq!(ring.reversed().get(cells - 2), Glyph::X);
+        assert_eq!(ring.canonicalize().to_base3()[..3], *"221");
+        assert_eq!(ring, ring.transform(Dihedral::Reflect(5)));
+
+        let board = BoardBuilder::new(cells).set(3, Glyph::X).build().unwrap();
+        assert_eq!(board.turn(), Glyph::O);
+    }
+}
+
+#[test]
+fn base3() {
+    assert_eq!(ring("00120").to_base3(), "00120");
+    assert_eq!(ring("22222222").to_base3(), "22222222");
+
+    for digits in ["0", "01201201", "00000002", "12121212121212121212"] {
+        let ring = Ring::from_base3(digits).unwrap();
+        assert_eq!(ring.int, self::ring(digits).int);
+        assert_eq!(ring.to_base3(), digits);
+    }
+
+    assert_eq!(Ring::from_base3("").err(), Some(ParseRingError::Empty));
+    assert_eq!(
+        Ring::from_base3(&"012".repeat(27)).err(),
+        Some(ParseRingError::TooLong(81))
+    );
+    assert_eq!(
+        Ring::from_base3("0130").err(),
+        Some(ParseRingError::InvalidChar('3'))
+    );
+}
+
+#[test]
+fn literal() {
+    assert_eq!(ring("00000012"), ring("12000000"));
+    assert_ne!(Literal(ring("00000012")), Literal(ring("12000000")));
+    assert_eq!(Literal(ring("00000012")), Literal(ring("00000012")));
+    // Same digits, but a different number of cells.
+    assert_ne!(Literal(ring("012")), Literal(ring("0012")));
+
+    let rings: HashSet<_> = [ring("00000012"), ring("12000000"), ring("21000000")]
+        .iter()
+        .copied()
+        .collect();
+    assert_eq!(rings.len(), 1);
+
+    let literals: HashSet<_> = [ring("00000012"), ring("12000000"), ring("21000000")]
+        .iter()
+        .copied()
+        .map(Literal)
+        .collect();
+    assert_eq!(literals.len(), 3);
+}
+
+#[test]
+fn canonical_ring() {
+    let a = CanonicalRing::new(ring("01200000"));
+    let reflected = CanonicalRing::from(ring("00000210"));
+    let different = CanonicalRing::new(ring("01100000"));
+
+    assert!(a == reflected);
+    assert!(a != different);
+    assert_eq!(hash(a), hash(reflected));
+    assert_eq!(hash(a), hash(ring("01200000")));
+    assert_eq!(a.cmp(&different), ring("01200000").cmp(&ring("01100000")));
+
+    assert_eq!(reflected.ring().to_base3(), "00000210");
+    assert_eq!(reflected.canonical().to_base3(), "21000000");
+
+    let set: HashSet<_> = [a, reflected, different].iter().copied().collect();
+    assert_eq!(set.len(), 2);
+}
+
+#[test]
+fn multiring() {
+    assert_eq!(
+        MultiRing::new(&[8, 12]).err(),
+        Some(BoardError::MisalignedRings {
+            inner: 8,
+            outer: 12
+        })
+    );
+    assert_eq!(MultiRing::new(&[]).err(), Some(BoardError::NoCells));
+    assert_eq!(
+        MultiRing::new(&[5, 10]).err(),
+        Some(BoardError::OddCells(5))
+    );
+
+    // With one ring, it's the same as a normal board.
+    assert_eq!(MultiRing::new(&[8]).unwrap().lines().len(), 8 + 4);
+
+    let mut board = MultiRing::new(&[4, 8]).unwrap();
+    // 4 + 8 lines around the rings, and 3 along each of the 2 spokes.
+    assert_eq!(board.lines().len(), 4 + 8 + 2 * 3);
+
+    // Around the outer ring.
+    board.rings[1] = ring("11100000");
+    assert_eq!(board.winner(), Some(Player::X));
+
+    // Outwards from the center along a spoke: inner cell 1 lines up with outer cell 2.
+    let mut board = MultiRing::new(&[4, 8]).unwrap();
+    board.center = Glyph::O;
+    board.set(Place::Ring { ring: 0, index: 1 }, Glyph::O);
+    assert_eq!(board.winner(), None);
+    board.set(Place::Ring { ring: 1, index: 2 }, Glyph::O);
+    assert_eq!(board.winner(), Some(Player::O));
+    assert_eq!(board.wins().len(), 1);
+
+    // Across the center, without reaching the outer ring on the far side.
+    let mut board = MultiRing::new(&[4, 8]).unwrap();
+    board.center = Glyph::X;
+    board.rings[0] = ring("1010");
+    assert_eq!(board.winner(), Some(Player::X));
+}
+
+#[test]
+fn unwinnable() {
+    assert!(!Board::new(8).is_unwinnable());
+
+    // There are still 3 empty cells, but every line has both an X and an O in it.
+    let board = Board {
+        center: Glyph::None,
+        ring: ring("01210212"),
+    };
+    assert_eq!(board.result(), GameResult::InProgress);
+    assert!(board.is_unwinnable());
+
+    // X can still win through the center.
+    let board = Board {
+        center: Glyph::None,
+        ring: ring("01210210"),
+    };
+    assert!(!board.is_unwinnable());
+}
+
+#[test]
+fn lines() {
+    let lines = Board::new(8).lines();
+    assert_eq!(lines.len(), 8 + 4);
+    assert!(lines.contains(&Line::Ring { index: 7 }));
+    assert!(lines.contains(&Line::Center { index: 3 }));
+    assert!(!lines.contains(&Line::Center { index: 4 }));
+
+    assert_eq!(
+        Line::Ring { index: 7 }.cells(8),
+        [Position::Ring(7), Position::Ring(0), Position::Ring(1)]
+    );
+    assert_eq!(
+        Line::Center { index: 3 }.cells(8),
+        [Position::Ring(3), Position::Center, Position::Ring(7)]
+    );
+}
+
+#[test]
+fn odd_rings() {
+    let lines = Board::new(7).lines();
+    assert_eq!(lines.len(), 7 + 7);
+    // Each cell has a line to both of the cells either side of the one opposite it.
+    assert_eq!(
+        Line::Center { index: 0 }.cells(7),
+        [Position::Ring(0), Position::Center, Position::Ring(3)]
+    );
+    assert_eq!(
+        Line::Center { index: 4 }.cells(7),
+        [Position::Ring(4), Position::Center, Position::Ring(0)]
+    );
+
+    let board = Board {
+        center: Glyph::O,
+        ring: ring("2001000"),
+    };
+    assert_eq!(board.winner(), None);
+    let board = Board {
+        center: Glyph::O,
+        ring: ring("2002000"),
+    };
+    assert_eq!(board.winner(), Some(Player::O));
+    assert_eq!(
+        board.wins(),
+        [Win::Center {
+            index: 0,
+            winner: Player::O
+        }]
+    );
+    let board = Board {
+        center: Glyph::O,
+        ring: ring("2000200"),
+    };
+    assert_eq!(
+        board.wins(),
+        [Win::Center {
+            index: 4,
+            winner: Player::O
+        }]
+    );
+
+    let board = Board {
+        center: Glyph::None,
+        ring: ring("1101110"),
+    };
+    let win = Win::Ring {
+        index: 3,
+        length: 3,
+        winner: Player::X,
+    };
+    assert_eq!(board.wins(), [win]);
+    assert_eq!(win.winner(), Player::X);
+    assert_eq!(
+        win.cells(7).collect::<Vec<_>>(),
+        [3, 4, 5].map(Position::Ring)
+    );
+    assert_eq!(
+        Win::Center {
+            index: 4,
+            winner: Player::O
+        }
+        .cells(7)
+        .collect::<Vec<_>>(),
+        [Position::Ring(4), Position::Center, Position::Ring(0)]
+    );
+
+    // Both parities should still play through to the end.
+    for cells in [3, 5] {
+        let stats = selfplay::self_play(cells, 1, selfplay::perfect, selfplay::perfect);
+        assert_eq!(stats.games(), 1);
+    }
+}
+
+#[test]
+fn near_wins() {
+    let board = Board {
+        center: Glyph::None,
+        ring: ring("11002200"),
+    };
+    assert_eq!(
+        board.near_wins(Player::X),
+        [Position::Ring(2), Position::Ring(7)]
+    );
+    assert_eq!(
+        board.near_wins(Player::O),
+        [Position::Ring(3), Position::Ring(6)]
+    );
+
+    let board = Board {
+        center: Glyph::None,
+        ring: ring("00110010"),
+    };
+    assert_eq!(
+        board.near_wins(Player::X),
+        [Position::Ring(1), Position::Ring(4), Position::Center]
+    );
+    assert_eq!(board.near_wins(Player::O), []);
+
+    // Cell 1 finishes off two lines at once, but should only be there once.
+    let board = Board {
+        center: Glyph::X,
+        ring: ring("10100100"),
+    };
+    assert_eq!(
+        board.near_wins(Player::X),
+        [Position::Ring(1), Position::Ring(4), Position::Ring(6)]
+    );
+}
+
+#[test]
+fn threats() {
+    let board = Board {
+        center: Glyph::None,
+        ring: ring("11002000"),
+    };
+    assert_eq!(
+        board.threats(Player::X),
+        [Position::Ring(2), Position::Ring(7)]
+    );
+    assert_eq!(selfplay::blocking(&board), Position::Ring(2));
+
+    let board = Board {
+        center: Glyph::None,
+        ring: ring("11002020"),
+    };
+    assert_eq!(selfplay::blocking(&board), Position::Ring(2));
+
+    // Once somebody's won, nobody's got any more moves to threaten anything with.
+    let board = Board {
+        center: Glyph::None,
+        ring: ring("11102200"),
+    };
+    assert_eq!(
+        board.near_wins(Player::O),
+        [Position::Ring(3), Position::Ring(6)]
+    );
+    assert_eq!(board.threats(Player::O), []);
+}
+
+#[test]
+fn macros() {
+    assert!(Literal(crate::ring!("XO  X O ")) == Literal("XO  X O ".parse().unwrap()));
+    assert_eq!(crate::ring!(" ").len(), 1);
+
+    let board = crate::board!(center: X, ring: "OO X");
+    assert!(board.is_identical(&"X|OO X".parse().unwrap()));
+    assert!(crate::board!(ring: "   ").is_identical(&Board::new(3)));
+}
+
+#[test]
+fn board_cells() {
+    let board = Board {
+        center: Glyph::O,
+        ring: ring("102"),
+    };
+    assert_eq!(
+        board.cells().collect::<Vec<_>>(),
+        [
+            (Position::Center, Glyph::O),
+            (Position::Ring(0), Glyph::X),
+            (Position::Ring(1), Glyph::None),
+            (Position::Ring(2), Glyph::O),
+        ]
+    );
+}
+
+#[test]
+fn checked_accessors() {
+    let mut ring = Ring::new(6);
+    assert_eq!(ring.set_checked(5, Glyph::X), Ok(()));
+    assert_eq!(
+        ring.set_checked(6, Glyph::O),
+        Err(MoveError::OutOfRange { index: 6, cells: 6 })
+    );
+    assert_eq!(ring.get_checked(5), Some(Glyph::X));
+    assert_eq!(ring.get_checked(6), None);
+    assert_eq!(ring.count(Glyph::O), 0);
+
+    assert_eq!(ring.get_wrapping(11), Glyph::X);
+    ring.set_wrapping(6, Glyph::O);
+    assert_eq!(ring.get_checked(0), Some(Glyph::O));
+}
+
+#[test]
+fn set_all() {
+    let mut ring = Ring::new(8);
+    ring.set_all([(0, Glyph::X), (3, Glyph::O), (9, Glyph::X)]);
+    assert_eq!(ring.to_string(), "XX O    ");
+
+    assert_eq!(
+        ring.try_set_all([(2, Glyph::O), (8, Glyph::O)]),
+        Err(BoardError::OutOfRange { index: 8, cells: 8 })
+    );
+    // Nothing should have been set, not even the valid index.
+    assert_eq!(ring.to_string(), "XX O    ");
+
+    ring.try_set_all([(2, Glyph::O), (7, Glyph::O)]).unwrap();
+    assert_eq!(ring.to_string(), "XXOO   O");
+}
+
+#[test]
+fn try_from_str() {
+    assert_eq!(
+        Ring::try_from("01201201").unwrap().int,
+        ring("01201201").int
+    );
+    assert_eq!(
+        Ring::try_from(" XO XO X").unwrap().int,
+        ring("01201201").int
+    );
+
+    // Digits and glyphs can't be mixed.
+    assert_eq!(
+        Ring::try_from(" X0").err(),
+        Some(ParseRingError::InvalidChar('0'))
+    );
+    assert_eq!(
+        Ring::try_from("X?").err(),
+        Some(ParseRingError::InvalidChar('?'))
+    );
+    assert_eq!(
+        Ring::try_from("XO".repeat(41).as_str()).err(),
+        Some(ParseRingError::TooLong(82))
+    );
+    assert_eq!(Ring::try_from("").err(), Some(ParseRingError::Empty));
+}
+
+#[test]
+fn from_str() {
+    for digits in [
+        "0",
+        "01201201",
+        "00000000",
+        "22222222",
+        "1000000000000000000000002",
+    ] {
+        let ring = ring(digits);
+        let parsed: Ring = ring.to_string().parse().unwrap();
+        assert!(Literal(parsed) == Literal(ring));
+    }
+
+    assert_eq!(" XO".parse::<Ring>().unwrap().to_base3(), "012");
+    assert_eq!("".parse::<Ring>().err(), Some(ParseRingError::Empty));
+    // Digits and lowercase glyphs aren't what `Display` outputs.
+    assert_eq!(
+        "012".parse::<Ring>().err(),
+        Some(ParseRingError::InvalidChar('0'))
+    );
+    assert_eq!(
+        "Xo".parse::<Ring>().err(),
+        Some(ParseRingError::InvalidChar('o'))
+    );
+}
+
+#[test]
+fn board_from_str() {
+    let board = Board {
+        center: Glyph::X,
+        ring: ring("01201201"),
+    };
+    assert_eq!(board.to_string(), "X| XO XO X");
+    assert_eq!(Board::new(4).to_string(), " |    ");
+
+    for s in ["X| XO XO X", " |    ", "O|XXXXXXX", "X|O"] {
+        let board: Board = s.parse().unwrap();
+        assert_eq!(board.to_string(), s);
+    }
+
+    assert_eq!("X|".parse::<Board>().err(), Some(ParseRingError::Empty));
+    assert_eq!(
+        "X XO".parse::<Board>().err(),
+        Some(ParseRingError::NoCenter)
+    );
+    assert_eq!("".parse::<Board>().err(), Some(ParseRingError::NoCenter));
+    assert_eq!(
+        "?| XO".parse::<Board>().err(),
+        Some(ParseRingError::InvalidChar('?'))
+    );
+    assert_eq!(
+        "X| Xo".parse::<Board>().err(),
+        Some(ParseRingError::InvalidChar('o'))
+    );
+}
+
+#[test]
+fn canonical_index() {
+    // These are the numbers of 3-colour bracelets of each length.
+    assert_eq!(
+        Ring::from_canonical_index(4, 20).map(|ring| ring.int),
+        Some(80)
+    );
+    assert!(Ring::from_canonical_index(4, 21).is_none());
+    assert!(Ring::from_canonical_index(6, 91).is_some());
+    assert!(Ring::from_canonical_index(6, 92).is_none());
+
+    for index in 0..92 {
+        let ring = Ring::from_canonical_index(6, index).unwrap();
+        assert_eq!(ring.canonical_index(), index);
+        assert_eq!((ring << 2).reversed().canonical_index(), index);
+    }
+
+    assert_eq!(Ring::new(8).canonical_index(), 0);
+}
+
+#[test]
+fn board_canonical_index() {
+    for cells in 1..=7 {
+        let count = Board::canonical_count(cells);
+        assert_eq!(count, Ring::canonical_count(cells) * 3);
+        assert!(Board::from_canonical_index(cells, count).is_none());
+
+        let mut seen = HashSet::new();
+        for index in 0..count {
+            let board = Board::from_canonical_index(cells, index).unwrap();
+            assert_eq!(board.canonical_index(), index);
+            assert_eq!(
+                board.transform(Dihedral::Reflect(1)).canonical_index(),
+                index
+            );
+            assert!(seen.insert(board));
+        }
+    }
+
+    // Every board should be in there somewhere.
+    for int in 0..3u128.pow(5) {
+        for center in [Glyph::None, Glyph::X, Glyph::O] {
+            let board = Board {
+                center,
+                ring: Ring { int, cells: 5 },
+            };
+            let index = board.canonical_index();
+            assert!(Board::from_canonical_index(5, index).unwrap() == board);
+        }
+    }
+}
+
+#[test]
+fn enumerate() {
+    assert_eq!(enumerate::canonical_rings(6).count(), 92);
+    assert!(enumerate::canonical_rings(6).all(|ring| Literal(ring) == Literal(ring.canonicalize())));
+    assert_eq!(enumerate::canonical_boards(6).count(), 92 * 3);
+
+    // Check the reachable boards against actually playing out every game.
+    for cells in 3..=6 {
+        let mut seen = HashSet::new();
+        let mut stack = vec![Board::new(cells)];
+        while let Some(board) = stack.pop() {
+            if seen.insert(board) {
+                stack.extend(
+                    board
+                        .available_moves()
+                        .into_iter()
+                        .map(|mv| board.with_move(mv).unwrap()),
+                );
+            }
+        }
+
+        let reachable: HashSet<_> = enumerate::reachable_boards(cells).collect();
+        assert_eq!(reachable.len(), enumerate::reachable_boards(cells).count());
+        assert!(reachable == seen);
+    }
+}
+
+#[test]
+fn burnside() {
+    for cells in 1..=9 {
+        assert_eq!(
+            enumerate::count_canonical_rings(cells),
+            enumerate::canonical_rings(cells).count() as u128
+        );
+        assert_eq!(
+            enumerate::count_canonical_boards(cells),
+            enumerate::canonical_boards(cells).count() as u128
+        );
+    }
+
+    // The biggest ring shouldn't overflow, and there should be roughly 3^80 / 160 of them.
+    let count = enumerate::count_canonical_rings(Ring::MAX_CELLS);
+    assert!(count > 3u128.pow(80) / 160 && count < 3u128.pow(80) / 159);
+}
+
+#[test]
+fn geometry() {
+    use std::f32::consts::PI;
+
+    for cells in [3, 8, 13] {
+        for rotation in [0.0, 1.0, -5.0, 20.0] {
+            for i in 0..cells {
+                let angle = geometry::index_to_angle(i, cells, rotation);
+                assert_eq!(geometry::angle_to_index(angle, cells, rotation), i);
+
+                // Anywhere inside the cell's arc should count as that cell.
+                let (start, end) = geometry::cell_arc(i, cells, rotation);
+                assert_eq!(geometry::angle_to_index(start + 0.01, cells, rotation), i);
+                assert_eq!(geometry::angle_to_index(end - 0.01, cells, rotation), i);
+            }
+        }
+    }
+
+    let layout = Layout {
+        center_radius: 100.0,
+        ring_inner_radius: 105.0,
+        ring_outer_radius: 300.0,
+    };
+    assert_eq!(layout.hit_test(10.0, -20.0, 8, 0.0), Some(Position::Center));
+    assert_eq!(layout.hit_test(102.0, 0.0, 8, 0.0), None);
+    assert_eq!(layout.hit_test(0.0, 200.0, 8, 0.0), Some(Position::Ring(2)));
+    assert_eq!(
+        layout.hit_test(0.0, 200.0, 8, PI / 2.0),
+        Some(Position::Ring(0))
+    );
+    assert_eq!(layout.hit_test(-400.0, 0.0, 8, 0.0), None);
+}
+
+#[test]
+fn error_messages() {
+    assert_eq!(
+        MoveError::OutOfRange { index: 9, cells: 8 }.to_string(),
+        "index 9 out of range for 8-cell ring"
+    );
+    assert_eq!(
+        BoardError::TooManyCells(82).to_string(),
+        "82 cells is too many; rings can have at most 80"
+    );
+    assert_eq!(
+        ParseRingError::InvalidChar('?').to_string(),
+        "'?' isn't a valid cell"
+    );
+}
+
+#[test]
+fn cells_nth() {
+    let ring = ring("0120120120");
+    for n in 0..12 {
+        let mut fast = ring.iter();
+        let mut slow = ring.iter();
+        for _ in 0..n {
+            slow.next();
+        }
+        assert_eq!(fast.nth(n), slow.next());
+        // The iterators should carry on in sync afterwards, from both ends.
+        assert_eq!(fast.next_back(), slow.next_back());
+        assert_eq!(fast.collect::<Vec<_>>(), slow.collect::<Vec<_>>());
+    }
+
+    for n in 0..12 {
+        let mut fast = ring.iter();
+        let mut slow = ring.iter();
+        for _ in 0..n {
+            slow.next_back();
+        }
+        assert_eq!(fast.nth_back(n), slow.next_back());
+        assert_eq!(fast.next(), slow.next());
+        assert_eq!(fast.collect::<Vec<_>>(), slow.collect::<Vec<_>>());
+    }
+
+    let mut cells = ring.iter();
+    assert_eq!(cells.nth(20), None);
+    assert_eq!(cells.next(), None);
+    assert_eq!(cells.next_back(), None);
+
+    let mut cells = ring.iter();
+    cells.next();
+    assert_eq!(cells.clone().count(), 9);
+    assert_eq!(cells.clone().last(), Some(Glyph::None));
+    cells.nth_back(0);
+    assert_eq!(cells.last(), Some(Glyph::O));
+}
+
+#[test]
+fn iter() {
+    let ring = ring("0120");
+    let expected = [Glyph::None, Glyph::X, Glyph::O, Glyph::None];
+    assert_eq!(ring.iter().collect::<Vec<_>>(), expected);
+    assert_eq!((&ring).into_iter().collect::<Vec<_>>(), expected);
+
+    let mut glyphs = Vec::new();
+    for glyph in &ring {
+        glyphs.push(glyph);
+    }
+    assert_eq!(glyphs, expected);
+}
+
+#[test]
+fn cells_len() {
+    let ring = ring("0120120");
+    let mut cells = ring.iter();
+    assert_eq!(cells.len(), 7);
+
+    let mut expected = 7;
+    for from_back in [false, true, true, false, true, false, false] {
+        let glyph = if from_back {
+            cells.next_back()
+        } else {
+            cells.next()
+        };
+        assert!(glyph.is_some());
+        expected -= 1;
+        assert_eq!(cells.len(), expected);
+        assert_eq!(cells.size_hint(), (expected, Some(expected)));
+    }
+
+    assert_eq!(cells.next(), None);
+    assert_eq!(cells.next_back(), None);
+    assert_eq!(cells.len(), 0);
+}
+
+#[test]
+fn dihedral() {
+    let ring = ring("01220000");
+    assert_eq!(
+        ring.transform(Dihedral::Rotate(1)).int,
+        self::ring("00122000").int
+    );
+    assert_eq!(
+        ring.transform(Dihedral::Reflect(7)).int,
+        ring.reversed().int
+    );
+    assert_eq!(
+        ring.transform(Dihedral::Reflect(2)).int,
+        self::ring("21000002").int
+    );
+
+    for d in Dihedral::all(8) {
+        let transformed = ring.transform(d);
+        for i in 0..8 {
+            assert_eq!(transformed.get(d.map_index(i, 8)), ring.get(i));
+        }
+    }
+
+    // Doing any two symmetries one after the other should be the same as doing some single symmetry.
+    for a in Dihedral::all(8) {
+        for b in Dihedral::all(8) {
+            let twice = ring.transform(a).transform(b);
+            assert!(Dihedral::all(8).any(|c| ring.transform(c).int == twice.int));
+        }
+    }
+
+    let board = Board {
+        center: Glyph::O,
+        ring,
+    }
+    .transform(Dihedral::Rotate(3));
+    assert_eq!(board.center, Glyph::O);
+    assert_eq!(board.ring.int, self::ring("00001220").int);
+}
+
+#[test]
+fn symmetry_to() {
+    let a = ring("01220000");
+    let b = ring("00002210");
+    let d = a.symmetry_to(&b).unwrap();
+    assert_eq!(a.transform(d).int, b.int);
+
+    assert_eq!(a.symmetry_to(&a), Some(Dihedral::Rotate(0)));
+    assert_eq!(a.symmetry_to(&ring("01210000")), None);
+    assert_eq!(a.symmetry_to(&ring("0122000")), None);
+}
+
+#[test]
+fn rotate_to_canonical() {
+    for a in [ring("01220000"), ring("10200001"), ring("00000000")] {
+        let (canonical, d) = a.rotate_to_canonical();
+        assert_eq!(canonical.int, a.canonicalize().int);
+        assert_eq!(a.transform(d).int, canonical.int);
+
+        // Every cell should be able to find its way back to where it started.
+        for i in 0..a.len() {
+            assert_eq!(canonical.get(d.map_index(i, a.len())), a.get(i));
+        }
+    }
+}
+
+/// The obvious way of canonicalizing a ring, to check the real one against.
+fn brute_force_canonical(ring: Ring) -> u128 {
+    ring.symmetries().map(|ring| ring.int).max().unwrap()
+}
+
+#[test]
+fn booth() {
+    for cells in 1..=8 {
+        for int in 0..3u128.pow(cells.into()) {
+            let ring = Ring { int, cells };
+            let (canonical, d) = ring.canonicalize_with_transform();
+            assert_eq!(canonical.int, brute_force_canonical(ring), "{:?}", ring);
+            assert_eq!(ring.transform(d).int, canonical.int);
+        }
+    }
+
+    // Some bigger ones, with lots of repetition to trip it up.
+    for digits in [
+        "1".repeat(80),
+        "01".repeat(40),
+        "0010".repeat(20),
+        "2".repeat(39) + "1",
+        "12".repeat(10) + &"0".repeat(19) + "2",
+    ] {
+        let ring = ring(&digits);
+        let (canonical, d) = ring.canonicalize_with_transform();
+        assert_eq!(canonical.int, brute_force_canonical(ring), "{}", digits);
+        assert_eq!(ring.transform(d).int, canonical.int);
+    }
+}
+
+#[test]
+fn symmetries() {
+    let ring = ring("0120");
+    let rotations: Vec<_> = ring.rotations().map(|ring| ring.to_base3()).collect();
+    assert_eq!(rotations, ["0120", "0012", "2001", "1200"]);
+
+    let symmetries: Vec<_> = ring.symmetries().collect();
+    assert_eq!(symmetries.len(), 8);
+    for (d, symmetry) in Dihedral::all(4).zip(&symmetries) {
+        assert_eq!(ring.transform(d).int, symmetry.int);
+    }
+    // Every one of them should be equal to the original, since equality ignores symmetry.
+    assert!(symmetries.iter().all(|&symmetry| symmetry == ring));
+
+    let unique: HashSet<_> = symmetries.iter().map(|&ring| Literal(ring)).collect();
+    assert_eq!(unique.len(), 8);
+    assert_eq!(self::ring("1010").symmetries().count(), 8);
+}
+
+#[test]
+fn invert() {
+    let ring = ring("0122010");
+    for d in Dihedral::all(7) {
+        let undo = d.invert(7);
+        assert_eq!(undo.apply(d.apply(ring)).int, ring.int);
+        for i in 0..7 {
+            assert_eq!(undo.map_index(d.map_index(i, 7), 7), i);
+        }
+    }
+
+    let (canonical, d) = ring.canonicalize_with_transform();
+    assert_eq!(d.apply(ring).int, canonical.int);
+    assert_eq!(d.invert(7).apply(canonical).int, ring.int);
+}
+
+#[test]
+fn arrays() {
+    let ring = Ring::from([Glyph::X, Glyph::None, Glyph::O, Glyph::O]);
+    assert_eq!(ring.int, self::ring("1022").int);
+    assert_eq!(ring.len(), 4);
+
+    assert_eq!(
+        ring.to_array(),
+        Some([Glyph::X, Glyph::None, Glyph::O, Glyph::O])
+    );
+    assert_eq!(ring.to_array::<5>(), None);
+}
+
+#[test]
+fn map() {
+    assert_eq!(
+        ring("01201201")
+            .map(|glyph| glyph.player().map(Player::opponent).into())
+            .int,
+        ring("02102102").int
+    );
+    assert_eq!(ring("01201201").map(|_| Glyph::X).int, ring("11111111").int);
+}
+
+#[test]
+fn clear() {
+    let mut board = Board {
+        center: Glyph::X,
+        ring: ring("11122000"),
+    };
+    board.clear();
+    assert_eq!(board.center, Glyph::None);
+    assert_eq!(board.ring.len(), 8);
+    assert_eq!(board.ring.count(Glyph::None), 8);
+    assert_eq!(board.result(), GameResult::InProgress);
+}
+
+#[test]
+fn board_eq() {
+    let a = Board {
+        center: Glyph::X,
+        ring: ring("01200000"),
+    };
+    let rotated = Board {
+        center: Glyph::X,
+        ring: ring("00000120"),
+    };
+    let different_center = Board {
+        center: Glyph::O,
+        ring: ring("01200000"),
+    };
+
+    assert!(a == rotated);
+    assert!(!a.is_identical(&rotated));
+
+    assert!(a != different_center);
+    assert!(!a.is_identical(&different_center));
+
+    assert!(a.is_identical(&a));
+
+    // Boards are `Copy`, so `a` is still usable after this.
+    let mut b = a;
+    b.play(Position::Ring(7)).unwrap();
+    assert!(!a.is_identical(&b));
+}
+
+#[test]
+fn board_hash() {
+    let a = Board {
+        center: Glyph::X,
+        ring: ring("01200000"),
+    };
+    let reflected = Board {
+        center: Glyph::X,
+        ring: ring("00000210"),
+    };
+    let different = Board {
+        center: Glyph::O,
+        ring: ring("01200000"),
+    };
+
+    assert_eq!(hash(a), hash(reflected));
+    assert_ne!(hash(a), hash(different));
+
+    assert!(a.canonicalize().is_identical(&reflected.canonicalize()));
+    assert!(!a.canonicalize().is_identical(&different.canonicalize()));
+    assert!(a.canonicalize() == a);
+
+    let boards: HashSet<_> = [a, reflected, different].iter().copied().collect();
+    assert_eq!(boards.len(), 2);
+}
+
+#[test]
+fn ord() {
+    use std::collections::BTreeSet;
+
+    assert!(ring("01200000") == ring("00000210"));
+    assert!(ring("0000") != ring("00000000"));
+
+    assert_eq!(ring("01200000").cmp(&ring("00000210")), Ordering::Equal);
+    assert!(ring("10000000") < ring("11000000"));
+    // Size comes first, even though the smaller ring has a bigger integer.
+    assert!(ring("2222") < ring("00000000"));
+
+    let x = Board {
+        center: Glyph::X,
+        ring: ring("01200000"),
+    };
+    let o = Board {
+        center: Glyph::O,
+        ring: ring("00000210"),
+    };
+    assert!(x < o);
+    assert!(Board::new(8) < x);
+
+    let set: BTreeSet<_> = [o, x, x.transform(Dihedral::Rotate(3)), Board::new(8)]
+        .iter()
+        .copied()
+        .collect();
+    assert_eq!(set.len(), 3);
+    assert!(set.iter().next().unwrap().is_identical(&Board::new(8)));
+}
+
+#[test]
+fn wins_iter() {
+    let board = Board {
+        center: Glyph::X,
+        ring: ring("1112201"),
+    };
+    assert_eq!(board.wins_iter().collect::<Vec<_>>(), board.wins());
+    assert_eq!(board.wins_iter().count(), 3);
+    assert_eq!(board.winner(), Some(Player::X));
+
+    assert_eq!(Board::new(6).wins_iter().next(), None);
+}
+
+#[test]
+fn wins_merged() {
+    let board = Board {
+        center: Glyph::None,
+        ring: ring("1102222100"),
+    };
+    assert_eq!(board.wins().len(), 2);
+    assert_eq!(
+        board.wins_merged(),
+        [Win::Ring {
+            index: 3,
+            length: 4,
+            winner: Player::O
+        }]
+    );
+
+    // Runs can wrap around the end of the ring.
+    let board = Board {
+        center: Glyph::X,
+        ring: ring("11022111"),
+    };
+    assert_eq!(
+        board.wins_merged(),
+        [
+            Win::Ring {
+                index: 5,
+                length: 5,
+                winner: Player::X
+            },
+            Win::Center {
+                index: 1,
+                winner: Player::X
+            },
+        ]
+    );
+    assert_eq!(board.wins_merged()[0].cells(8).count(), 5);
+
+    let board = Board {
+        center: Glyph::None,
+        ring: ring("222222"),
+    };
+    assert_eq!(
+        board.wins_merged(),
+        [Win::Ring {
+            index: 0,
+            length: 6,
+            winner: Player::O
+        }]
+    );
+}
+
+#[test]
+fn render_ascii() {
+    let board: Board = "X|XO   O X".parse().unwrap();
+    assert_eq!(
+        board.render_ascii(),
+        "    .\n\
+         \x20O     X\n\
+         .   X   X\n\
+         \x20.     O\n\
+         \x20   .\n"
+    );
+
+    for cells in 1..=Ring::MAX_CELLS {
+        let board = Board::new(cells);
+        let rendered = board.render_ascii();
+        assert_eq!(rendered.matches('.').count(), usize::from(cells) + 1);
+        assert_eq!(format!("{:#}", board), rendered);
+    }
+}
+
+#[cfg(feature = "ansi")]
+#[test]
+fn render_ansi() {
+    let board: Board = "X|XXX O O ".parse().unwrap();
+    let rendered = board.render_ansi();
+    // The three Xs around the ring won, but the one in the center isn't part of it.
+    assert_eq!(rendered.matches("\x1b[1;7;31mX").count(), 3);
+    assert_eq!(rendered.matches("\x1b[31mX").count(), 1);
+    assert_eq!(rendered.matches("\x1b[34mO").count(), 2);
+    assert_eq!(rendered.matches("\x1b[2m.").count(), 3);
+}
+
+#[cfg(feature = "image")]
+#[test]
+fn render_png() {
+    let board: Board = "X|XXX O O ".parse().unwrap();
+    let png = board.render_png(200, 100);
+    assert!(png.starts_with(b"\x89PNG\r\n\x1a\n"));
+    // The width and height are the first things in the header.
+    assert_eq!(png[16..24], [0, 0, 0, 200, 0, 0, 0, 100]);
+}
+
+#[test]
+fn describe() {
+    let board = Board {
+        center: Glyph::X,
+        ring: ring("11121020"),
+    };
+    assert_eq!(
+        board.describe(),
+        "center: [X]\n\
+         ring:   [XXXOX O ]\n\
+         result: X won\n\
+         win:    around the ring from index 0\n\
+         win:    through the center from index 0\n"
+    );
+}
+
+#[test]
+fn best_move_depth() {
+    // X can win straight away through the center, which even the shallowest search should see.
+    let board = Board {
+        center: Glyph::X,
+        ring: ring("10200020"),
+    };
+    assert_eq!(board.best_move_depth(1), Some(Position::Ring(4)));
+
+    // It's fast enough to use on a big, empty board.
+    let board = Board::new(20);
+    assert!(board.best_move_depth(2).is_some());
+
+    assert_eq!(
+        Board {
+            center: Glyph::None,
+            ring: ring("11102200"),
+        }
+        .best_move_depth(3),
+        None
+    );
+}
+
+/// A plain minimax search without any pruning, to compare against the real one.
+fn minimax(board: &mut Board, nodes: &mut u64) -> i16 {
+    *nodes += 1;
+
+    match board.result() {
+        GameResult::Won(_) => return -(WIN_SCORE + board.empty_cells() as i16),
+        GameResult::Draw => return 0,
+        GameResult::InProgress => {}
+    }
+
+    let turn = board.turn();
+    let mut best = -i16::MAX;
+    for mv in board.available_moves() {
+        board.set(mv, turn.glyph());
+        best = best.max(-minimax(board, nodes));
+        board.set(mv, Glyph::None);
+    }
+    best
+}
+
+#[test]
+fn alpha_beta() {
+    for board in [
+        Board::new(6),
+        Board {
+            center: Glyph::None,
+            ring: ring("10000000"),
+        },
+        Board {
+            center: Glyph::X,
+            ring: ring("20000000"),
+        },
+        Board {
+            center: Glyph::None,
+            ring: ring("11000002"),
+        },
+    ] {
+        let mut nodes = 0;
+        let mut best = None;
+        let mut best_score = -i16::MAX;
+        let mut scratch = board;
+        for mv in board.available_moves() {
+            scratch.set(mv, board.turn().glyph());
+            let score = -minimax(&mut scratch, &mut nodes);
+            scratch.set(mv, Glyph::None);
+            if score > best_score {
+                best = Some(mv);
+                best_score = score;
+            }
+        }
+
+        let mut solver = Solver::new();
+        assert_eq!(solver.best_move(&board), best);
+        assert!(solver.nodes() < nodes);
+    }
+}
+
+#[test]
+fn solver_cache() {
+    let board = Board {
+        center: Glyph::None,
+        ring: ring("10000000"),
+    };
+
+    let mut solver = Solver::new();
+    let best = solver.best_move(&board);
+    let first_nodes = solver.nodes();
+    assert!(solver.cache_len() > 0);
+
+    // The second time around, the answers for all the positions right after this one should already be there.
+    assert_eq!(solver.best_move(&board), best);
+    assert!(solver.nodes() - first_nodes < first_nodes);
+
+    // The same goes for a rotation of the board, since the cache doesn't care about symmetry.
+    let rotated = Board {
+        center: Glyph::None,
+        ring: ring("00010000"),
+    };
+    let before = solver.nodes();
+    solver.best_move(&rotated);
+    assert!(solver.nodes() - before < first_nodes);
+
+    solver.clear();
+    assert_eq!(solver.cache_len(), 0);
+}
+
+#[cfg(feature = "net")]
+#[test]
+fn net() {
+    use std::net::TcpListener;
+    use std::thread;
+
+    use crate::net::Client;
+    use crate::net::Host;
+    use crate::net::Message;
+    use crate::net::NetError;
+
+    assert_eq!(
+        Message::parse("state 1 02000000").unwrap().to_string(),
+        "state 1 02000000"
+    );
+    assert_eq!(Message::parse("move c").unwrap().to_string(), "move c");
+    assert!(Message::parse("move x").is_none());
+    assert!(Message::parse("hello").is_none());
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let client = thread::spawn(move || {
+        let mut client = Client::connect(addr).unwrap();
+        assert_eq!(client.board().ring.len(), 8);
+        assert!(matches!(
+            client.play(Position::Ring(0)),
+            Err(NetError::NotYourTurn)
+        ));
+
+        client.wait().unwrap();
+        assert_eq!(client.board().center, Glyph::X);
+
+        // The center's already taken, so the host should refuse this.
+        assert!(matches!(
+            client.play(Position::Center),
+            Err(NetError::Rejected(_))
+        ));
+        client.play(Position::Ring(4)).unwrap();
+        assert_eq!(client.board().ring.to_base3(), "00002000");
+    });
+
+    let mut host = Host::accept(&listener, 8).unwrap();
+    host.play(Position::Center).unwrap();
+    assert!(matches!(
+        host.play(Position::Ring(0)),
+        Err(NetError::NotYourTurn)
+    ));
+    assert_eq!(host.wait().unwrap(), Position::Ring(4));
+    assert_eq!(host.board().ring.get(4), Glyph::O);
+
+    client.join().unwrap();
+}
+
+#[test]
+fn const_ring() {
+    // Every cell but the last one, worked out at compile time.
+    const FILLED: Ring = {
+        let mut ring = Ring::new(5);
+        let mut i = 0;
+        while i < 4 {
+            ring.set(i, Glyph::X);
+            i += 1;
+        }
+        ring
+    };
+    const ROTATED: Ring = FILLED.rotate_right(2);
+    const FIRST: Glyph = ROTATED.get(0);
+
+    assert!(Literal(FILLED) == Literal("XXXX ".parse().unwrap()));
+    assert!(Literal(ROTATED) == Literal("X XXX".parse().unwrap()));
+    assert!(Literal(FILLED << 3) == Literal(ROTATED));
+    assert_eq!(FIRST, Glyph::X);
+}
+
+#[cfg(feature = "arbitrary")]
+#[test]
+fn arbitrary() {
+    use arbitrary::Arbitrary;
+    use arbitrary::Unstructured;
+
+    let bytes: Vec<u8> = (0..4096u32).map(|i| (i * 37 % 251) as u8).collect();
+    let mut u = Unstructured::new(&bytes);
+    while !u.is_empty() {
+        let board = Board::arbitrary(&mut u).unwrap();
+        assert!(board.ring.cells >= 1 && board.ring.cells <= Ring::MAX_CELLS);
+        assert!(board.ring.int < 3u128.pow(board.ring.cells.into()));
+        // Make sure the ring's actually usable, and not just in range.
+        assert!(Literal((board.ring << 5) >> 5) == Literal(board.ring));
+    }
+}
+
+#[cfg(feature = "proptest")]
+mod properties {
+    use proptest::prelude::*;
+
+    use crate::solver::Solver;
+    use crate::strategies::any_board;
+    use crate::strategies::any_ring;
+    use crate::strategies::reachable_board;
+    use crate::Board;
+    use crate::Dihedral;
+    use crate::GameResult;
+    use crate::Literal;
+    use crate::Ring;
+
+    proptest! {
+        #[test]
+        fn symmetric_rings_are_equal(ring in any_ring(9), k in 0..9u8) {
+            for d in [Dihedral::Rotate(k), Dihedral::Reflect(k)] {
+                let transformed = ring.transform(d);
+                prop_assert!(transformed == ring);
+                prop_assert_eq!(super::hash(transformed), super::hash(ring));
+                prop_assert!(Literal(transformed.canonicalize()) == Literal(ring.canonicalize()));
+            }
+        }
+
+        #[test]
+        fn board_round_trips(board in any_board(8)) {
+            let parsed: Board = board.to_string().parse().unwrap();
+            prop_assert!(parsed.is_identical(&board));
+            let ring = Ring::from_base3(&board.ring.to_base3()).unwrap();
+            prop_assert!(Literal(ring) == Literal(board.ring));
+        }
+
+        #[test]
+        fn reachable_boards_are_reachable(board in reachable_board(6)) {
+            prop_assert!(board.is_reachable());
+        }
+
+        #[test]
+        fn solver_picks_legal_moves(board in reachable_board(6)) {
+            let best = Solver::new().best_move(&board);
+            prop_assert_eq!(best.is_some(), board.result() == GameResult::InProgress);
+            if let Some(mv) = best {
+                prop_assert!(board.legal_moves(board.turn()).any(|pos| pos == mv));
+            }
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn serde() {
+    let board: Board = "O|XXXO  O ".parse().unwrap();
+    let json = serde_json::to_string(&board).unwrap();
+    assert_eq!(json, r#"{"center":"O","ring":"XXXO  O "}"#);
+    assert!(serde_json::from_str::<Board>(&json)
+        .unwrap()
+        .is_identical(&board));
+
+    let win = board.wins()[0];
+    assert_eq!(
+        serde_json::from_str::<Win>(&serde_json::to_string(&win).unwrap()).unwrap(),
+        win
+    );
+    let mv = Move::Place {
+        pos: Position::Ring(3),
+        player: Player::O,
+        glyph: Glyph::O,
+    };
+    assert_eq!(
+        serde_json::from_str::<Move>(&serde_json::to_string(&mv).unwrap()).unwrap(),
+        mv
+    );
+
+    // Rings get checked the same way as when they're parsed.
+    assert!(serde_json::from_str::<Ring>(r#""XOZ""#).is_err());
+    assert!(serde_json::from_str::<Ring>(r#""""#).is_err());
+    assert!(serde_json::from_str::<Board>(r#"{"center":"Y","ring":"XO  "}"#).is_err());
+}
+
+#[test]
+fn bytes() {
+    let board: Board = "O|XXXO  O ".parse().unwrap();
+    let bytes = board.to_bytes();
+    assert_eq!(bytes.len(), 5);
+    assert_eq!(bytes[..3], [Board::ENCODING_VERSION, 8, 2]);
+    assert!(Board::from_bytes(&bytes).unwrap().is_identical(&board));
+
+    for cells in [1, 5, 20, Ring::MAX_CELLS] {
+        let mut board = Board::new(cells);
+        board.ring.set_all((0..cells).map(|i| (i, Glyph::O)));
+        let bytes = board.to_bytes();
+        assert!(Board::from_bytes(&bytes).unwrap().is_identical(&board));
+    }
+
+    assert_eq!(Board::from_bytes(&[]), Err(DecodeError::TooShort));
+    assert_eq!(Board::from_bytes(&bytes[..4]), Err(DecodeError::TooShort));
+    assert_eq!(
+        Board::from_bytes(&[&bytes[..], &[0]].concat()),
+        Err(DecodeError::TooLong)
+    );
+    assert_eq!(
+        Board::from_bytes(&[2, 8, 0, 0, 0]),
+        Err(DecodeError::UnknownVersion(2))
+    );
+    assert_eq!(
+        Board::from_bytes(&[1, 0]),
+        Err(DecodeError::Board(BoardError::NoCells))
+    );
+    assert_eq!(
+        Board::from_bytes(&[1, 8, 3, 0, 0]),
+        Err(DecodeError::InvalidCenter(3))
+    );
+    // 3^8 is 6561, which is 0x19a1.
+    assert_eq!(
+        Board::from_bytes(&[1, 8, 0, 0xa1, 0x19]),
+        Err(DecodeError::RingOutOfRange)
+    );
+}
+
+#[test]
+fn codes() {
+    let board: Board = "O|XXXO  O ".parse().unwrap();
+    let code = board.to_code();
+    assert_eq!(code.len(), 7);
+    assert!(code
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'));
+    assert!(Board::from_code(&code).unwrap().is_identical(&board));
+    assert!(Board::from_code(&format!(" {}\n", code))
+        .unwrap()
+        .is_identical(&board));
+
+    for cells in 1..=Ring::MAX_CELLS {
+        let mut board = Board::new(cells);
+        board.center = Glyph::X;
+        board
+            .ring
+            .set_all((0..cells).step_by(3).map(|i| (i, Glyph::O)));
+        assert!(Board::from_code(&board.to_code())
+            .unwrap()
+            .is_identical(&board));
+    }
+
+    assert_eq!(Board::from_code("AQ!"), Err(DecodeError::InvalidChar('!')));
+    assert_eq!(Board::from_code(""), Err(DecodeError::TooShort));
+
+    // Every board only has one code, so nothing can be set past the end of it.
+    let last = CODE_ALPHABET
+        .iter()
+        .position(|&digit| code.ends_with(char::from(digit)))
+        .unwrap();
+    // 8 cells take 5 bytes, which leaves 2 bits spare at the end of the 7th character.
+    let padded = format!(
+        "{}{}",
+        &code[..code.len() - 1],
+        char::from(CODE_ALPHABET[last | 1])
+    );
+    assert_eq!(Board::from_code(&padded), Err(DecodeError::TooLong));
+    assert_eq!(
+        Board::from_code(&format!("{}A", code)),
+        Err(DecodeError::TooLong)
+    );
+    // 3 isn't a valid ring with only 1 cell.
+    let code = encode_code(&[Board::ENCODING_VERSION, 1, 0, 3]);
+    assert_eq!(Board::from_code(&code), Err(DecodeError::RingOutOfRange));
+}
+
+#[test]
+fn diff() {
+    let before: Board = " |X    O  ".parse().unwrap();
+    let after: Board = " |X  X O  ".parse().unwrap();
+    assert_eq!(
+        before.diff(&after),
+        Ok(Move::Place {
+            pos: Position::Ring(3),
+            player: Player::X,
+            glyph: Glyph::X,
+        })
+    );
+    assert_eq!(
+        before.diff(&"X|X    O  ".parse().unwrap()),
+        Ok(Move::Place {
+            pos: Position::Center,
+            player: Player::X,
+            glyph: Glyph::X,
+        })
+    );
+
+    assert_eq!(before.diff(&before), Err(DiffError::NoChange));
+    assert_eq!(
+        after.diff(&before),
+        Err(DiffError::NotAPlacement(Position::Ring(3)))
+    );
+    assert_eq!(
+        before.diff(&"O|X  X O  ".parse().unwrap()),
+        Err(DiffError::TooManyChanges(2))
+    );
+    assert_eq!(
+        before.diff(&" |X  O O  ".parse().unwrap()),
+        Err(DiffError::Illegal(MoveError::WrongTurn))
+    );
+    assert_eq!(
+        before.diff(&Board::new(6)),
+        Err(DiffError::DifferentSizes {
+            before: 8,
+            after: 6
+        })
+    );
+    // A rotation of the same board doesn't count as the same.
+    assert_eq!(
+        before.diff(&before.transform(Dihedral::Rotate(1))),
+        Err(DiffError::TooManyChanges(4))
+    );
+}
+
+#[test]
+#[allow(deprecated)]
+fn glyph_chars() {
+    for glyph in [Glyph::None, Glyph::X, Glyph::O] {
+        assert_eq!(Glyph::from_char(glyph.to_char()), Some(glyph));
+        assert_eq!(glyph.to_string(), glyph.to_char().to_string());
+    }
+    assert_eq!(Glyph::from_char('x'), None);
+    assert_eq!(Glyph::O.to_string(), "O");
+
+    // The old name still works.
+    let cell: crate::Cell = crate::Cell::X;
+    assert_eq!(cell, Glyph::X);
+}
+
+#[test]
+fn all_win_lines() {
+    let lines = Board::all_win_lines(6);
+    // 6 around the ring, and 3 through the center.
+    assert_eq!(lines.len(), 9);
+    assert_eq!(lines[0], [0, 1, 2].map(Position::Ring));
+    assert_eq!(lines[5], [5, 0, 1].map(Position::Ring));
+    assert_eq!(
+        lines[6],
+        [Position::Ring(0), Position::Center, Position::Ring(3)]
+    );
+    assert_eq!(Board::all_win_lines(5).len(), 10);
+
+    // They're the same lines that the board checks for wins.
+    let board = Board::new(7);
+    assert_eq!(
+        board
+            .lines()
+            .into_iter()
+            .map(|line| line.cells(7))
+            .collect::<Vec<_>>(),
+        Board::all_win_lines(7)
+    );
+    assert_eq!(Line::all(8).count(), 12);
+}
+
+#[test]
+fn game() {
+    let mut game = Game::new(RuleSet::new(8));
+    assert_eq!(game.turn(), Player::X);
+    assert_eq!(
+        game.play(Position::Center),
+        Ok(Move::Place {
+            pos: Position::Center,
+            player: Player::X,
+            glyph: Glyph::X,
+        })
+    );
+    assert_eq!(game.turn(), Player::O);
+
+    // A failed move doesn't use up the turn.
+    assert_eq!(game.play(Position::Center), Err(MoveError::Occupied));
+    assert_eq!(
+        game.play(Position::Ring(8)),
+        Err(MoveError::OutOfRange { index: 8, cells: 8 })
+    );
+    assert_eq!(
+        game.apply(Move::Place {
+            pos: Position::Ring(0),
+            player: Player::X,
+            glyph: Glyph::X,
+        }),
+        Err(MoveError::WrongTurn)
+    );
+    assert_eq!(game.turn(), Player::O);
+
+    for pos in [4, 0, 5, 1, 7, 2].map(Position::Ring) {
+        game.play(pos).unwrap();
+    }
+    assert_eq!(game.status(), GameStatus::Won(Player::X));
+    assert!(game.is_over());
+    assert_eq!(game.play(Position::Ring(5)), Err(MoveError::GameOver));
+    assert_eq!(game.board().to_string(), "X|XXX OO O");
+
+    game.restart();
+    assert_eq!(game.turn(), Player::X);
+    assert_eq!(game.status(), GameStatus::InProgress);
+    assert!(game.board().is_identical(&Board::new(8)));
+
+    // Picking up from a board works out whose turn it is.
+    let game = Game::from_board(" |X    O  ".parse().unwrap());
+    assert_eq!(game.turn(), Player::X);
+    assert_eq!(
+        Game::try_new(RuleSet::new(0)).unwrap_err(),
+        RuleError::Board(BoardError::NoCells)
+    );
+}
+
+#[test]
+fn game_history() {
+    let mut game = Game::new(RuleSet::new(8));
+    assert_eq!(game.undo(), None);
+    assert_eq!(game.redo(), None);
+
+    let first = game.play(Position::Center).unwrap();
+    let second = game.play(Position::Ring(3)).unwrap();
+    assert_eq!(game.history(), [first, second]);
+
+    assert_eq!(game.undo(), Some(second));
+    assert_eq!(game.turn(), Player::O);
+    assert_eq!(game.history(), [first]);
+    assert_eq!(game.undo(), Some(first));
+    assert_eq!(game.undo(), None);
+    assert!(game.board().is_identical(&Board::new(8)));
+
+    assert_eq!(game.redo(), Some(first));
+    assert_eq!(game.redo(), Some(second));
+    assert_eq!(game.redo(), None);
+    assert_eq!(game.board().to_string(), "X|   O    ");
+    assert_eq!(game.turn(), Player::X);
+
+    // Playing something else throws away whatever was undone.
+    game.undo();
+    game.play(Position::Ring(5)).unwrap();
+    assert_eq!(game.redo(), None);
+    assert_eq!(game.history().len(), 2);
+
+    game.restart();
+    assert_eq!(game.history(), []);
+    assert_eq!(game.undo(), None);
+}
+
+#[test]
+fn game_events() {
+    let events = Rc::new(RefCell::new(Vec::new()));
+    let mut game = Game::new(RuleSet::new(4));
+    let log = Rc::clone(&events);
+    game.on_event(move |event| log.borrow_mut().push(event));
+
+    let mv = game.play(Position::Center).unwrap();
+    assert_eq!(*events.borrow(), [GameEvent::MovePlayed(mv)]);
+    // Failed moves don't count.
+    assert!(game.play(Position::Center).is_err());
+    assert_eq!(events.borrow().len(), 1);
+
+    game.undo();
+    game.redo();
+    assert_eq!(
+        events.borrow()[1..],
+        [GameEvent::Undone(mv), GameEvent::MovePlayed(mv)]
+    );
+
+    events.borrow_mut().clear();
+    for i in [0, 1, 2] {
+        game.play(Position::Ring(i)).unwrap();
+    }
+    let last = game.play(Position::Ring(3)).unwrap();
+    assert_eq!(game.board().to_string(), "X|OXOX");
+    assert_eq!(
+        events.borrow()[3..],
+        [GameEvent::MovePlayed(last), GameEvent::Won(Player::X)]
+    );
+
+    // Clones don't bring the listeners with them.
+    let mut clone = game.clone();
+    clone.undo();
+    assert_eq!(events.borrow().len(), 5);
+}
+
+#[test]
+fn rules() {
+    assert_eq!(RuleSet::default().validate(), Ok(()));
+    assert_eq!(RuleSet::new(2).validate(), Ok(()));
+    let rules = RuleSet {
+        win_length: 9,
+        ..RuleSet::new(8)
+    };
+    assert_eq!(
+        rules.validate(),
+        Err(RuleError::WinLength {
+            length: 9,
+            cells: 8
+        })
+    );
+    assert_eq!(
+        RuleSet::new(Ring::MAX_CELLS + 1).validate(),
+        Err(RuleError::Board(BoardError::TooManyCells(
+            Ring::MAX_CELLS + 1
+        )))
+    );
+
+    // Longer lines only count around the ring.
+    let rules = RuleSet {
+        win_length: 4,
+        ..RuleSet::new(8)
+    };
+    let board: Board = "X|XXXO    ".parse().unwrap();
+    assert_eq!(board.status(), GameStatus::Won(Player::X));
+    assert_eq!(rules.status(&board), GameStatus::InProgress);
+    assert_eq!(
+        rules.status(&"O|XXXXO  O".parse().unwrap()),
+        GameStatus::Won(Player::X)
+    );
+
+    // Without the center, the board's full once the ring is.
+    let rules = RuleSet {
+        center_enabled: false,
+        ..RuleSet::new(4)
+    };
+    let mut game = Game::new(rules);
+    assert_eq!(game.play(Position::Center), Err(MoveError::NotAllowed));
+    for i in 0..4 {
+        game.play(Position::Ring(i)).unwrap();
+    }
+    assert_eq!(game.board().to_string(), " |XOXO");
+    assert_eq!(game.status(), GameStatus::Draw);
+
+    let rules = RuleSet {
+        misere: true,
+        ..RuleSet::new(8)
+    };
+    assert_eq!(
+        rules.status(&"X|X   X   ".parse().unwrap()),
+        GameStatus::Won(Player::O)
+    );
+    assert_eq!(rules.variant, Variant::Standard);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn rules_serde() {
+    let rules = RuleSet {
+        misere: true,
+        ..RuleSet::default()
+    };
+    let json = serde_json::to_string(&rules).unwrap();
+    assert_eq!(
+        json,
+        r#"{"cells":8,"win_length":3,"center_enabled":true,"misere":true,"variant":"Standard"}"#
+    );
+    assert_eq!(serde_json::from_str::<RuleSet>(&json).unwrap(), rules);
+}
+
+#[test]
+fn rotation_variant() {
+    let rotate = |player| Move::Rotate {
+        player,
+        direction: Direction::Clockwise,
+    };
+    // The normal rules don't allow rotating.
+    let mut board = Board::new(8);
+    assert_eq!(board.apply(rotate(Player::X)), Err(MoveError::NotAllowed));
+    let mut game = Game::default();
+    assert_eq!(
+        game.rotate(Direction::Clockwise),
+        Err(MoveError::NotAllowed)
+    );
+
+    let rules = RuleSet {
+        variant: Variant::Rotation,
+        ..RuleSet::new(8)
+    };
+    let mut game = Game::new(rules);
+    game.play(Position::Ring(0)).unwrap();
+    assert_eq!(game.rotate(Direction::Clockwise), Ok(rotate(Player::O)));
+    assert_eq!(game.board().to_string(), " | X      ");
+    assert_eq!(game.turn(), Player::X);
+    assert_eq!(game.apply(rotate(Player::O)), Err(MoveError::WrongTurn));
+
+    // Turning it straight back would put the board back how it was with O to move.
+    assert_eq!(
+        game.rotate(Direction::Anticlockwise),
+        Err(MoveError::Repetition)
+    );
+    game.rotate(Direction::Clockwise).unwrap();
+    assert_eq!(game.board().to_string(), " |  X     ");
+
+    assert_eq!(game.undo(), Some(rotate(Player::X)));
+    assert_eq!(game.undo(), Some(rotate(Player::O)));
+    assert_eq!(game.board().to_string(), " |X       ");
+    assert_eq!(game.turn(), Player::O);
+    assert_eq!(game.redo(), Some(rotate(Player::O)));
+    assert_eq!(game.history().len(), 2);
+
+    assert_eq!(Direction::Clockwise.reverse(), Direction::Anticlockwise);
+    let ring: Ring = "X O ".parse().unwrap();
+    assert_eq!(
+        Literal(Direction::Anticlockwise.apply(Direction::Clockwise.apply(ring))),
+        Literal(ring)
+    );
+}
+
+#[test]
+fn misere_solver() {
+    let board: Board = " |XX  OO  ".parse().unwrap();
+    let winning = [Position::Ring(2), Position::Ring(7)];
+    assert!(winning.contains(&Solver::new().best_move(&board).unwrap()));
+
+    // In misère, completing the line would lose straight away.
+    let mv = Solver::misere().best_move(&board).unwrap();
+    assert!(!winning.contains(&mv));
+    assert_eq!(board.with_move(mv).unwrap().winner(), None);
+
+    let mut game = Game::new(RuleSet {
+        misere: true,
+        ..RuleSet::new(8)
+    });
+    for pos in [0, 4, 1, 5].map(Position::Ring) {
+        game.play(pos).unwrap();
+    }
+    assert!(!winning.contains(&game.best_move().unwrap()));
+    game.play(Position::Ring(2)).unwrap();
+    assert_eq!(game.status(), GameStatus::Won(Player::O));
+
+    // The solver only knows how to play the normal game, so it doesn't suggest anything for the others.
+    assert!(Game::default().best_move().is_some());
+    let rules = [
+        RuleSet {
+            center_enabled: false,
+            ..RuleSet::new(8)
+        },
+        RuleSet {
+            win_length: 4,
+            ..RuleSet::new(8)
+        },
+        RuleSet {
+            variant: Variant::Morris { pieces: 3 },
+            ..RuleSet::new(8)
+        },
+    ];
+    for rules in rules {
+        assert_eq!(Game::new(rules).best_move(), None);
+    }
+}
+
+#[test]
+fn order_and_chaos() {
+    // Normally you can only put down your own glyph.
+    let mut game = Game::default();
+    assert_eq!(
+        game.place(Position::Center, Glyph::O),
+        Err(MoveError::NotAllowed)
+    );
+    let mut board = Board::new(8);
+    assert_eq!(
+        board.apply(Move::Place {
+            pos: Position::Center,
+            player: Player::X,
+            glyph: Glyph::O,
+        }),
+        Err(MoveError::NotAllowed)
+    );
+
+    let rules = RuleSet {
+        variant: Variant::OrderAndChaos,
+        ..RuleSet::new(4)
+    };
+    let mut game = Game::new(rules);
+    assert_eq!(
+        game.place(Position::Center, Glyph::None),
+        Err(MoveError::NotAllowed)
+    );
+    // Order completing a line of Os still counts as a win for Order.
+    game.place(Position::Ring(0), Glyph::O).unwrap();
+    game.place(Position::Ring(1), Glyph::O).unwrap();
+    assert_eq!(
+        game.place(Position::Ring(2), Glyph::O),
+        Ok(Move::Place {
+            pos: Position::Ring(2),
+            player: Player::X,
+            glyph: Glyph::O,
+        })
+    );
+    assert_eq!(game.status(), GameStatus::Won(Player::X));
+
+    // If the board fills up without a line, Chaos wins instead of it being a draw.
+    let board: Board = "X|XOOX".parse().unwrap();
+    assert_eq!(board.status(), GameStatus::Draw);
+    assert_eq!(rules.status(&board), GameStatus::Won(Player::O));
+}
+
+#[test]
+fn fading_variant() {
+    let rules = RuleSet {
+        variant: Variant::Fading { limit: 3 },
+        ..RuleSet::new(8)
+    };
+    let mut game = Game::new(rules);
+    for pos in [0, 4, 2, 6, 5, 7].map(Position::Ring) {
+        game.play(pos).unwrap();
+    }
+    assert_eq!(game.board().to_string(), " |X X OXOO");
+    assert!(game.placed(Player::X).eq([0, 2, 5].map(Position::Ring)));
+
+    // X's fourth glyph takes their first one away.
+    game.play(Position::Ring(3)).unwrap();
+    assert_eq!(game.board().to_string(), " |  XXOXOO");
+    assert!(game.placed(Player::X).eq([2, 5, 3].map(Position::Ring)));
+
+    game.play(Position::Center).unwrap();
+    assert_eq!(game.board().to_string(), "O|  XX XOO");
+
+    // X's line through 1, 2 and 3 would need their oldest glyph, which goes before the new one counts.
+    game.play(Position::Ring(1)).unwrap();
+    assert_eq!(game.board().to_string(), "O| X X XOO");
+    assert_eq!(game.status(), GameStatus::InProgress);
+
+    game.undo();
+    assert_eq!(game.board().to_string(), "O|  XX XOO");
+    assert!(game.placed(Player::X).eq([2, 5, 3].map(Position::Ring)));
+    assert!(game
+        .placed(Player::O)
+        .eq([Position::Ring(6), Position::Ring(7), Position::Center]));
+
+    assert_eq!(
+        RuleSet {
+            variant: Variant::Fading { limit: 2 },
+            ..RuleSet::new(8)
+        }
+        .validate(),
+        Err(RuleError::FadingLimit {
+            limit: 2,
+            win_length: 3
+        })
+    );
+    // 4 glyphs each would fill up all 8 ring cells when the center's turned off, but not with it.
+    let rules = RuleSet {
+        variant: Variant::Fading { limit: 4 },
+        ..RuleSet::new(8)
+    };
+    assert_eq!(rules.validate(), Ok(()));
+    assert_eq!(
+        RuleSet {
+            center_enabled: false,
+            ..rules
+        }
+        .validate(),
+        Err(RuleError::FadingFills { limit: 4, cells: 8 })
+    );
+}
+
+#[test]
+fn morris_variant() {
+    assert!(Position::Ring(0).is_adjacent(Position::Ring(7), 8));
+    assert!(Position::Ring(3).is_adjacent(Position::Center, 8));
+    assert!(Position::Center.is_adjacent(Position::Ring(3), 8));
+    assert!(!Position::Ring(3).is_adjacent(Position::Ring(5), 8));
+    assert!(!Position::Center.is_adjacent(Position::Center, 8));
+
+    let rules = RuleSet {
+        variant: Variant::Morris { pieces: 3 },
+        ..RuleSet::new(8)
+    };
+    let mut game = Game::new(rules);
+    assert_eq!(
+        game.slide(Position::Ring(0), Position::Ring(1)),
+        Err(MoveError::NotAllowed)
+    );
+    for pos in [0, 4, 2, 6, 5].map(Position::Ring) {
+        game.play(pos).unwrap();
+    }
+    game.play(Position::Center).unwrap();
+    assert_eq!(game.board().to_string(), "O|X X OXO ");
+
+    // Now that everything's been put down, it can only be moved around.
+    assert_eq!(game.play(Position::Ring(1)), Err(MoveError::NotAllowed));
+    assert_eq!(
+        game.slide(Position::Ring(4), Position::Ring(3)),
+        Err(MoveError::NoPiece)
+    );
+    assert_eq!(
+        game.slide(Position::Ring(0), Position::Ring(3)),
+        Err(MoveError::NotAdjacent)
+    );
+    assert_eq!(
+        game.slide(Position::Ring(5), Position::Ring(4)),
+        Err(MoveError::Occupied)
+    );
+
+    // Going back and forth brings the same position up a third time.
+    for _ in 0..2 {
+        game.slide(Position::Ring(0), Position::Ring(1)).unwrap();
+        game.slide(Position::Ring(6), Position::Ring(7)).unwrap();
+        assert_eq!(game.status(), GameStatus::InProgress);
+        game.slide(Position::Ring(1), Position::Ring(0)).unwrap();
+        game.slide(Position::Ring(7), Position::Ring(6)).unwrap();
+    }
+    assert_eq!(game.board().to_string(), "O|X X OXO ");
+    assert_eq!(game.status(), GameStatus::Draw);
+    assert!(game
+        .placed(Player::O)
+        .eq([Position::Ring(4), Position::Ring(6), Position::Center]));
+
+    let mut hash = Board::new(8).zobrist();
+    for mv in game.history() {
+        hash = zobrist::update(hash, *mv);
+    }
+    assert_eq!(hash, game.board().zobrist());
+
+    // With the center turned off, it can't be slid into either, so X's stuck here with O all around the empty cell.
+    let rules = RuleSet {
+        center_enabled: false,
+        variant: Variant::Morris { pieces: 3 },
+        ..RuleSet::new(7)
+    };
+    assert!(!rules.allows(Move::Slide {
+        player: Player::X,
+        from: Position::Ring(0),
+        to: Position::Center
+    }));
+    let mut game = Game::new(rules);
+    for pos in [1, 5, 2, 0, 4, 3].map(Position::Ring) {
+        game.play(pos).unwrap();
+    }
+    assert_eq!(game.board().to_string(), " |OXXOXO ");
+    assert_eq!(game.status(), GameStatus::Won(Player::O));
+    let mut game = Game::new(RuleSet {
+        center_enabled: true,
+        ..rules
+    });
+    for pos in [1, 5, 2, 0, 4, 3].map(Position::Ring) {
+        game.play(pos).unwrap();
+    }
+    assert_eq!(game.status(), GameStatus::InProgress);
+    game.slide(Position::Ring(4), Position::Center).unwrap();
+    assert_eq!(
+        RuleSet {
+            center_enabled: false,
+            variant: Variant::Morris { pieces: 4 },
+            ..RuleSet::new(8)
+        }
+        .validate(),
+        Err(RuleError::Pieces {
+            pieces: 4,
+            cells: 8
+        })
+    );
+
+    assert_eq!(
+        RuleSet {
+            variant: Variant::Morris { pieces: 5 },
+            ..RuleSet::new(8)
+        }
+        .validate(),
+        Err(RuleError::Pieces {
+            pieces: 5,
+            cells: 8
+        })
+    );
+}
+
+#[test]
+fn multiplayer() {
+    let seat = |index| Seat::new(index).unwrap();
+    assert_eq!(seat(2).to_char(), 'Y');
+    assert_eq!(Seat::from_char('Z'), Some(seat(3)));
+    assert_eq!(Seat::new(4), None);
+
+    assert_eq!(MultiGame::new(8, &[]).unwrap_err(), SetupError::NoPlayers);
+    assert_eq!(
+        MultiGame::new(8, &[seat(2)]).unwrap_err(),
+        SetupError::TooFewPlayers(seat(2))
+    );
+    assert_eq!(
+        MultiGame::new(8, &[seat(1), seat(0), seat(1)]).unwrap_err(),
+        SetupError::Duplicate(seat(1))
+    );
+    assert_eq!(
+        MultiGame::new(0, &[seat(0), seat(1)]).unwrap_err(),
+        SetupError::Board(BoardError::NoCells)
+    );
+
+    let players = [seat(0), seat(1), seat(2)];
+    let mut game = MultiGame::new(8, &players).unwrap();
+    let moves = [
+        Position::Ring(0),
+        Position::Center,
+        Position::Ring(5),
+        Position::Ring(2),
+        Position::Ring(3),
+        Position::Ring(6),
+        Position::Ring(4),
+        Position::Ring(1),
+    ];
+    for (i, &pos) in moves.iter().enumerate() {
+        assert_eq!(game.turn(), players[i % 3]);
+        game.play(pos).unwrap();
+    }
+    assert_eq!(game.play(Position::Ring(1)), Err(MoveError::Occupied));
+    assert_eq!(
+        game.play(Position::Ring(8)),
+        Err(MoveError::OutOfRange { index: 8, cells: 8 })
+    );
+    assert_eq!(game.status(), MultiStatus::InProgress);
+
+    game.play(Position::Ring(7)).unwrap();
+    assert_eq!(game.board().to_string(), "O|XOXOXYYY");
+    assert_eq!(game.board().wins(), [Line::Ring { index: 5 }]);
+    assert_eq!(game.status(), MultiStatus::Won(seat(2)));
+    assert_eq!(game.play(Position::Ring(7)), Err(MoveError::GameOver));
+
+    assert!(game.is_over());
+
+    assert_eq!(game.undo(), Some(Position::Ring(7)));
+    assert_eq!(game.turn(), seat(2));
+    assert_eq!(game.history(), moves);
+    assert_eq!(game.redo(), Some(Position::Ring(7)));
+    assert_eq!(game.status(), MultiStatus::Won(seat(2)));
+    assert_eq!(game.redo(), None);
+
+    // Playing something else after undoing means the undone move can't be redone.
+    game.undo();
+    assert_eq!(game.undo(), Some(Position::Ring(1)));
+    assert_eq!(game.turn(), seat(1));
+    game.play(Position::Ring(7)).unwrap();
+    assert_eq!(game.board().to_string(), "O|X XOXYYO");
+    assert_eq!(game.redo(), None);
+
+    game.restart();
+    assert!(game.history().is_empty());
+    assert_eq!(game.turn(), seat(0));
+    assert_eq!(game.board().to_string(), " |        ");
+
+    let board: MultiBoard = "Z|XOY Z".parse().unwrap();
+    assert_eq!(board.get(Position::Ring(4)), Some(seat(3)));
+    assert_eq!(board.get(Position::Ring(3)), None);
+    assert_eq!(board.to_string(), "Z|XOY Z");
+    assert_eq!(
+        "X|XOW".parse::<MultiBoard>(),
+        Err(ParseRingError::InvalidChar('W'))
+    );
+}
+
+#[test]
+fn ultimate() {
+    // 0 is the center, and everything else is a ring cell one before it.
+    let pos = |i: u8| match i {
+        0 => Position::Center,
+        i => Position::Ring(i - 1),
+    };
+    let mv = |(board, cell)| UltimateMove {
+        board: pos(board),
+        cell: pos(cell),
+    };
+    let moves = [
+        (0, 0),
+        (0, 1),
+        (1, 1),
+        (1, 0),
+        (0, 2),
+        (2, 0),
+        (0, 4),
+        (4, 0),
+        (1, 2),
+        (2, 1),
+        (1, 3),
+        (3, 0),
+        (3, 3),
+        (3, 1),
+        (3, 4),
+        (4, 1),
+        (3, 2),
+    ]
+    .map(mv);
+
+    let mut game = UltimateGame::new(4).unwrap();
+    assert_eq!(game.next_board(), None);
+    assert_eq!(game.legal_moves().len(), 25);
+    game.play(moves[0]).unwrap();
+    assert_eq!(game.next_board(), Some(Position::Center));
+    assert_eq!(game.play(mv((1, 0))), Err(MoveError::WrongBoard));
+    assert_eq!(game.play(mv((0, 0))), Err(MoveError::Occupied));
+    assert_eq!(
+        game.play(UltimateMove {
+            board: Position::Ring(4),
+            cell: Position::Center
+        }),
+        Err(MoveError::OutOfRange { index: 4, cells: 4 })
+    );
+
+    for &mv in &moves[1..7] {
+        game.play(mv).unwrap();
+    }
+    // X has just won the center board, and O sends them back to it.
+    assert_eq!(game.outer().to_string(), "X|    ");
+    game.play(moves[7]).unwrap();
+    assert!(game.is_decided(Position::Center));
+    assert_eq!(game.next_board(), None);
+    assert_eq!(game.play(mv((0, 3))), Err(MoveError::WrongBoard));
+    assert!(game
+        .legal_moves()
+        .iter()
+        .all(|mv| mv.board != Position::Center));
+
+    for &mv in &moves[8..16] {
+        game.play(mv).unwrap();
+    }
+    assert_eq!(game.status(), GameStatus::InProgress);
+    game.play(moves[16]).unwrap();
+    assert_eq!(game.outer().to_string(), "X|X X ");
+    assert_eq!(game.status(), GameStatus::Won(Player::X));
+    assert_eq!(game.play(mv((4, 4))), Err(MoveError::GameOver));
+    assert!(game.legal_moves().is_empty());
+    assert_eq!(game.history(), moves);
+
+    assert_eq!(game.undo(), Some(moves[16]));
+    assert_eq!(game.outer().to_string(), "X|X   ");
+    assert_eq!(game.turn(), Player::X);
+    assert_eq!(game.status(), GameStatus::InProgress);
+    assert!(game.legal_moves().contains(&moves[16]));
+}
+
+#[test]
+fn clock() {
+    let mut clock = Clock::new(Duration::from_secs(10), Duration::from_secs(2)).unwrap();
+    assert!(clock.spend(Player::X, Duration::from_secs(3)));
+    clock.finish_move(Player::X);
+    assert_eq!(clock.remaining(Player::X), Duration::from_secs(9));
+    assert_eq!(clock.remaining(Player::O), Duration::from_secs(10));
+    assert_eq!(clock.flagged(), None);
+    assert!(!clock.spend(Player::O, Duration::from_secs(11)));
+    clock.finish_move(Player::O);
+    assert_eq!(clock.remaining(Player::O), Duration::ZERO);
+    assert_eq!(clock.flagged(), Some(Player::O));
+    clock.reset();
+    assert_eq!(clock.remaining(Player::O), Duration::from_secs(10));
+
+    let mut clock = Clock::new(Duration::MAX, Duration::MAX).unwrap();
+    clock.finish_move(Player::X);
+    assert_eq!(clock.remaining(Player::X), Duration::MAX);
+
+    assert_eq!(Clock::new(Duration::ZERO, Duration::from_secs(1)), None);
+
+    let events = Rc::new(RefCell::new(Vec::new()));
+    let mut game = Game::default();
+    game.set_clock(Clock::new(Duration::from_secs(5), Duration::from_secs(1)).unwrap());
+    let sink = Rc::clone(&events);
+    game.on_event(move |event| sink.borrow_mut().push(event));
+
+    assert_eq!(game.tick(Duration::from_secs(2)), GameStatus::InProgress);
+    game.play(Position::Center).unwrap();
+    assert_eq!(
+        game.clock().unwrap().remaining(Player::X),
+        Duration::from_secs(4)
+    );
+    // Taking the move back doesn't give back the time, and redoing it doesn't give another increment.
+    game.undo().unwrap();
+    game.redo().unwrap();
+    assert_eq!(
+        game.clock().unwrap().remaining(Player::X),
+        Duration::from_secs(4)
+    );
+
+    game.tick(Duration::from_secs(3));
+    assert_eq!(
+        game.tick(Duration::from_secs(2)),
+        GameStatus::Won(Player::X)
+    );
+    assert_eq!(game.play(Position::Ring(0)), Err(MoveError::GameOver));
+    assert_eq!(
+        events.borrow().last(),
+        Some(&GameEvent::OutOfTime(Player::O))
+    );
+
+    // Once it's over, the clock stops.
+    game.tick(Duration::from_secs(10));
+    assert_eq!(
+        game.clock().unwrap().remaining(Player::X),
+        Duration::from_secs(4)
+    );
+
+    // Running out of time after taking a move back means it can't be redone anymore.
+    game.restart();
+    game.play(Position::Center).unwrap();
+    game.undo().unwrap();
+    assert_eq!(
+        game.tick(Duration::from_secs(10)),
+        GameStatus::Won(Player::O)
+    );
+    assert_eq!(game.redo(), None);
+
+    game.restart();
+    assert_eq!(game.status(), GameStatus::InProgress);
+    assert_eq!(
+        game.clock().unwrap().remaining(Player::O),
+        Duration::from_secs(5)
+    );
 }
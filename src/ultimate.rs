@@ -0,0 +1,168 @@
+//! Ultimate ring-tac-toe, where every cell of the board has a whole small board of its own inside it.
+//!
+//! Winning one of the small boards claims the cell it's in on the outer board, and getting a line of those on the
+//! outer board wins the game. The catch is that the cell you play in on a small board picks which small board your
+//! opponent has to play on next; if that one's already been decided, they can play on any small board that hasn't.
+
+use alloc::vec::Vec;
+
+use crate::Board;
+use crate::BoardError;
+use crate::GameStatus;
+use crate::Glyph;
+use crate::MoveError;
+use crate::Player;
+use crate::Position;
+
+/// A move in ultimate ring-tac-toe: which small board it's on, and which cell of that board it's in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct UltimateMove {
+    /// Where the small board is on the outer board.
+    pub board: Position,
+    /// Where the glyph goes on the small board. This is also where on the outer board the next player has to play.
+    pub cell: Position,
+}
+
+/// A game of ultimate ring-tac-toe.
+///
+/// The small boards are the same size as the outer board, so that every cell of a small board has a matching small
+/// board to send the next player to.
+#[derive(Debug, Clone)]
+pub struct UltimateGame {
+    /// Which player has claimed each cell by winning the small board in it.
+    outer: Board,
+    /// The small boards, starting with the center one and then going around the ring.
+    boards: Vec<Board>,
+    turn: Player,
+    history: Vec<UltimateMove>,
+}
+
+impl UltimateGame {
+    /// Start a new game where the outer board and every small board have `cells` around the outside.
+    pub fn new(cells: u8) -> Result<Self, BoardError> {
+        let outer = Board::try_new(cells)?;
+        Ok(Self {
+            outer,
+            boards: alloc::vec![outer; usize::from(cells) + 1],
+            turn: Player::X,
+            history: Vec::new(),
+        })
+    }
+
+    /// The outer board, where each cell has the glyph of whoever won the small board in it.
+    ///
+    /// Cells whose small board hasn't been won yet, or was drawn, are empty.
+    pub fn outer(&self) -> &Board {
+        &self.outer
+    }
+
+    /// Get the small board at `pos` on the outer board.
+    ///
+    /// Panics if `pos` is past the end of the ring.
+    pub fn board(&self, pos: Position) -> &Board {
+        &self.boards[self.index(pos).expect("index out of range")]
+    }
+
+    fn index(&self, pos: Position) -> Result<usize, MoveError> {
+        self.outer.get(pos)?;
+        Ok(match pos {
+            Position::Center => 0,
+            Position::Ring(i) => usize::from(i) + 1,
+        })
+    }
+
+    pub fn turn(&self) -> Player {
+        self.turn
+    }
+
+    /// Every move that's been made so far, oldest first.
+    pub fn history(&self) -> &[UltimateMove] {
+        &self.history
+    }
+
+    /// Whether the small board at `pos` has been won or filled up, so that it can't be played on any more.
+    pub fn is_decided(&self, pos: Position) -> bool {
+        self.board(pos).result() != GameStatus::InProgress
+    }
+
+    /// The small board the next move has to be on, or `None` if it can be on any of them that haven't been decided.
+    pub fn next_board(&self) -> Option<Position> {
+        let last = self.history.last()?;
+        Some(last.cell).filter(|&pos| !self.is_decided(pos))
+    }
+
+    /// Whether the game is still going, and if not, how it ended.
+    ///
+    /// It's a draw once every small board has been decided without anyone getting a line on the outer board.
+    pub fn status(&self) -> GameStatus {
+        match self.outer.winner() {
+            Some(player) => GameStatus::Won(player),
+            None if self.outer.cells().all(|(pos, _)| self.is_decided(pos)) => GameStatus::Draw,
+            None => GameStatus::InProgress,
+        }
+    }
+
+    /// Get all the moves which can currently be made, or nothing if the game is over.
+    pub fn legal_moves(&self) -> Vec<UltimateMove> {
+        if self.status() != GameStatus::InProgress {
+            return Vec::new();
+        }
+        let boards: Vec<Position> = match self.next_board() {
+            Some(pos) => alloc::vec![pos],
+            None => self
+                .outer
+                .cells()
+                .map(|(pos, _)| pos)
+                .filter(|&pos| !self.is_decided(pos))
+                .collect(),
+        };
+        boards
+            .into_iter()
+            .flat_map(|board| {
+                self.board(board)
+                    .cells()
+                    .filter(|(_, glyph)| glyph.is_empty())
+                    .map(move |(cell, _)| UltimateMove { board, cell })
+            })
+            .collect()
+    }
+
+    /// Put the glyph of whoever's turn it is at `mv`, claiming its small board on the outer board if that wins it.
+    pub fn play(&mut self, mv: UltimateMove) -> Result<(), MoveError> {
+        if self.status() != GameStatus::InProgress {
+            return Err(MoveError::GameOver);
+        }
+        let index = self.index(mv.board)?;
+        if self.boards[index].get(mv.cell)?.is_occupied() {
+            return Err(MoveError::Occupied);
+        }
+        if self.is_decided(mv.board) || self.next_board().is_some_and(|pos| pos != mv.board) {
+            return Err(MoveError::WrongBoard);
+        }
+
+        let board = &mut self.boards[index];
+        board.set(mv.cell, self.turn.glyph());
+        if board.winner() == Some(self.turn) {
+            self.outer.set(mv.board, self.turn.glyph());
+        }
+        self.history.push(mv);
+        self.turn = self.turn.opponent();
+        Ok(())
+    }
+
+    /// Take back the last move, giving the turn back to whoever made it.
+    ///
+    /// Returns the move, or `None` if there weren't any moves to take back.
+    pub fn undo(&mut self) -> Option<UltimateMove> {
+        let mv = self.history.pop()?;
+        let index = self
+            .index(mv.board)
+            .expect("history has a move out of range");
+        // The small board can't have been decided before this move, so if it's decided now this move is what did it.
+        self.boards[index].set(mv.cell, Glyph::None);
+        self.outer.set(mv.board, Glyph::None);
+        self.turn = self.turn.opponent();
+        Some(mv)
+    }
+}
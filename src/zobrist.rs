@@ -0,0 +1,87 @@
+//! Zobrist hashing of boards, for transposition tables that want something cheaper than `Board`'s symmetry-aware
+//! `Hash`.
+//!
+//! Every combination of a position and a glyph gets its own random key, and a board's hash is all the keys for the
+//! glyphs on it XORed together (plus one for the size of the ring). XOR is its own inverse, so making or taking back a
+//! move only needs one more XOR rather than hashing the whole board again.
+
+use crate::Board;
+use crate::Glyph;
+use crate::Move;
+use crate::Position;
+use crate::Ring;
+
+/// The keys for X and O in the center, followed by the keys for X and O at each index of the ring.
+static KEYS: [[u64; 2]; Ring::MAX_CELLS as usize + 1] = keys();
+
+/// The keys for each size of ring, so that boards of different sizes with the same glyphs don't collide.
+static SIZE_KEYS: [u64; Ring::MAX_CELLS as usize + 1] = size_keys();
+
+/// Generate the next number from a splitmix64 generator with the given state.
+///
+/// The keys don't need to be very random, they just need to be fixed and not have any obvious patterns.
+const fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9e3779b97f4a7c15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+    z ^ (z >> 31)
+}
+
+const fn keys() -> [[u64; 2]; Ring::MAX_CELLS as usize + 1] {
+    let mut state = 0;
+    let mut keys = [[0; 2]; Ring::MAX_CELLS as usize + 1];
+    let mut i = 0;
+    while i < keys.len() {
+        keys[i] = [splitmix64(&mut state), splitmix64(&mut state)];
+        i += 1;
+    }
+    keys
+}
+
+const fn size_keys() -> [u64; Ring::MAX_CELLS as usize + 1] {
+    // Start from a different state to `keys`, so that none of these are the same as any of those.
+    let mut state = u64::MAX / 3;
+    let mut keys = [0; Ring::MAX_CELLS as usize + 1];
+    let mut i = 0;
+    while i < keys.len() {
+        keys[i] = splitmix64(&mut state);
+        i += 1;
+    }
+    keys
+}
+
+/// Get the key for `glyph` being at `pos`. An empty cell doesn't change the hash, so its key is 0.
+pub fn key(pos: Position, glyph: Glyph) -> u64 {
+    let index = match pos {
+        Position::Center => 0,
+        Position::Ring(i) => usize::from(i) + 1,
+    };
+    match glyph {
+        Glyph::None => 0,
+        Glyph::X => KEYS[index][0],
+        Glyph::O => KEYS[index][1],
+    }
+}
+
+/// Work out the hash of `board` from scratch.
+pub fn hash(board: &Board) -> u64 {
+    board.cells().fold(
+        SIZE_KEYS[usize::from(board.ring.len())],
+        |hash, (pos, glyph)| hash ^ key(pos, glyph),
+    )
+}
+
+/// Update `hash` for `mv` being made or taken back; since this is just an XOR, it's the same either way.
+///
+/// Panics if `mv` is a `Move::Rotate`, since that moves every glyph on the ring and the hash has to be worked out from
+/// scratch.
+pub fn update(hash: u64, mv: Move) -> u64 {
+    match mv {
+        Move::Place { pos, glyph, .. } => hash ^ key(pos, glyph),
+        Move::Slide { player, from, to } => {
+            hash ^ key(from, player.glyph()) ^ key(to, player.glyph())
+        }
+        Move::Rotate { .. } => panic!("can't update a hash for rotating the ring"),
+    }
+}